@@ -0,0 +1,188 @@
+use crate::{error::ParseError, solution::Answer};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// Environment variable overriding the default answers file.
+pub const ANSWERS_FILE_VAR: &str = "AOC2020_ANSWERS";
+
+/// Expected answers of the real inputs, read from a small subset of TOML :
+///
+/// ```toml
+/// # comments and blank lines are ignored
+/// [day8]
+/// part1 = 1217
+/// part2 = 501
+///
+/// [day21]
+/// part2 = "lmzg,cxk,bsqh"
+/// ```
+///
+/// Text answers cannot contain `"` nor `#`.
+#[derive(Debug, Default, PartialEq)]
+pub struct Answers {
+    expected: HashMap<(u8, u8), Answer>,
+}
+
+/// Outcome of the comparison of an answer with the expected one.
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+    Pass,
+    Fail(Answer),
+    Missing,
+}
+
+impl Answers {
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut expected = HashMap::new();
+        let mut day = None;
+        for line in input.lines() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(section) = line.strip_prefix('[') {
+                let name = section
+                    .strip_suffix(']')
+                    .ok_or_else(|| ParseError::at(input, line, "expected `]` after the section"))?;
+                day = Some(parse_key(
+                    input,
+                    name,
+                    "day",
+                    25,
+                    "expected a section like [day8]",
+                )?);
+                continue;
+            }
+
+            let mut split = line.splitn(2, '=');
+            let key = split.next().unwrap().trim_end();
+            let value = split
+                .next()
+                .ok_or_else(|| ParseError::at(input, &line[line.len()..], "expected `=`"))?
+                .trim_start();
+            let day = day.ok_or_else(|| {
+                ParseError::at(input, key, "expected a [dayN] section before the answers")
+            })?;
+            let part = parse_key(input, key, "part", 2, "expected part1 or part2")?;
+            let answer = parse_value(input, value)?;
+            if expected.insert((day, part), answer).is_some() {
+                return Err(ParseError::at(input, key, "duplicate answer"));
+            }
+        }
+        Ok(Self { expected })
+    }
+
+    pub fn get(&self, day: u8, part: u8) -> Option<&Answer> {
+        self.expected.get(&(day, part))
+    }
+
+    /// Answers are compared as printed : the labels of day 23 are text made of digits and can be
+    /// written as an integer.
+    pub fn check(&self, day: u8, part: u8, answer: &Answer) -> Verdict {
+        match self.get(day, part) {
+            Some(expected) if expected.to_string() == answer.to_string() => Verdict::Pass,
+            Some(expected) => Verdict::Fail(expected.clone()),
+            None => Verdict::Missing,
+        }
+    }
+}
+
+/// Parses `dayN` or `partN` keys up to `max`, `key` being a slice of `input`.
+fn parse_key(
+    input: &str,
+    key: &str,
+    prefix: &str,
+    max: u8,
+    message: &str,
+) -> Result<u8, ParseError> {
+    key.strip_prefix(prefix)
+        .and_then(|number| number.parse().ok())
+        .filter(|&number| number > 0 && number <= max)
+        .ok_or_else(|| ParseError::at(input, key, message))
+}
+
+/// Integers are written as is, text answers between double quotes.
+fn parse_value(input: &str, value: &str) -> Result<Answer, ParseError> {
+    if let Some(text) = value.strip_prefix('"') {
+        return text
+            .strip_suffix('"')
+            .filter(|text| !text.contains('"'))
+            .map(|text| Answer::Text(text.to_owned()))
+            .ok_or_else(|| ParseError::at(input, value, "unterminated string"));
+    }
+    value
+        .parse()
+        .map(Answer::Integer)
+        .map_err(|_| ParseError::at(input, value, "expected an integer or a quoted string"))
+}
+
+/// The file given by `AOC2020_ANSWERS` if set, `answers.toml` at the root of the crate otherwise.
+pub fn default_path() -> PathBuf {
+    match std::env::var_os(ANSWERS_FILE_VAR) {
+        Some(path) => PathBuf::from(path),
+        None => Path::new(env!("CARGO_MANIFEST_DIR")).join("answers.toml"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let input =
+            "# real inputs\n[day8]\npart1 = 1217\npart2 = 501 # fixed\n\n[day21]\npart2 = \"a,b\"";
+        let answers = Answers::parse(input).unwrap();
+        assert_eq!(answers.get(8, 1), Some(&Answer::Integer(1217)));
+        assert_eq!(answers.get(8, 2), Some(&Answer::Integer(501)));
+        assert_eq!(answers.get(21, 2), Some(&Answer::Text("a,b".to_owned())));
+        assert_eq!(answers.get(21, 1), None);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert_eq!(
+            Answers::parse("[day8]\npart3 = 12"),
+            Err(ParseError::new(2, 1, "part3", "expected part1 or part2"))
+        );
+        assert_eq!(
+            Answers::parse("part1 = 12"),
+            Err(ParseError::new(
+                1,
+                1,
+                "part1",
+                "expected a [dayN] section before the answers"
+            ))
+        );
+        assert_eq!(
+            Answers::parse("[day8]\npart1 = 12\npart1 = 13"),
+            Err(ParseError::new(3, 1, "part1", "duplicate answer"))
+        );
+        assert_eq!(
+            Answers::parse("[day8]\npart1 = x"),
+            Err(ParseError::new(
+                2,
+                9,
+                "x",
+                "expected an integer or a quoted string"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_check() {
+        let answers = Answers::parse("[day1]\npart1 = 514579").unwrap();
+        assert_eq!(answers.check(1, 1, &Answer::Integer(514579)), Verdict::Pass);
+        assert_eq!(
+            answers.check(1, 1, &Answer::Integer(1)),
+            Verdict::Fail(Answer::Integer(514579))
+        );
+        assert_eq!(answers.check(1, 2, &Answer::Integer(1)), Verdict::Missing);
+        assert_eq!(
+            answers.check(1, 1, &Answer::Text("514579".to_owned())),
+            Verdict::Pass
+        );
+    }
+}
@@ -0,0 +1,236 @@
+use crate::{
+    error::{next_token, parse_token, ParseError},
+    solution::Puzzle,
+};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+/// Measured step of a day : parsing the input or solving a part from the parsed input.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Phase {
+    Parse,
+    Part(u8),
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Phase::Parse => write!(f, "parse"),
+            Phase::Part(part) => write!(f, "part{}", part),
+        }
+    }
+}
+
+impl Phase {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "parse" => Some(Phase::Parse),
+            _ => name
+                .strip_prefix("part")
+                .and_then(|part| part.parse().ok())
+                .map(Phase::Part),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+    pub stddev: Duration,
+}
+
+impl Stats {
+    /// Panics if there is no sample.
+    pub fn new(samples: &[Duration]) -> Self {
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        let count = sorted.len();
+        let median = if count.is_multiple_of(2) {
+            (sorted[count / 2 - 1] + sorted[count / 2]) / 2
+        } else {
+            sorted[count / 2]
+        };
+        // nearest rank
+        let p95 = sorted[(count * 95).div_ceil(100) - 1];
+        let mean = sorted.iter().map(Duration::as_secs_f64).sum::<f64>() / count as f64;
+        let variance = sorted
+            .iter()
+            .map(|sample| (sample.as_secs_f64() - mean).powi(2))
+            .sum::<f64>()
+            / count as f64;
+        Self {
+            min: sorted[0],
+            median,
+            p95,
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchResult {
+    pub day: u8,
+    pub phase: Phase,
+    pub stats: Stats,
+}
+
+/// Runs every phase of the day `iterations` times, the parts are solved from a single parse.
+pub fn bench(
+    puzzle: &dyn Puzzle,
+    input: &str,
+    iterations: usize,
+) -> Result<Vec<BenchResult>, ParseError> {
+    let sample = |f: &dyn Fn()| {
+        (0..iterations)
+            .map(|_| {
+                let timer = Instant::now();
+                f();
+                timer.elapsed()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // fails early on invalid input so the closure below can unwrap
+    let parsed = puzzle.parse(input)?;
+    let mut results = vec![BenchResult {
+        day: puzzle.day(),
+        phase: Phase::Parse,
+        stats: Stats::new(&sample(&|| {
+            puzzle.parse(input).unwrap();
+        })),
+    }];
+    for part in 1..=puzzle.parts() {
+        results.push(BenchResult {
+            day: puzzle.day(),
+            phase: Phase::Part(part),
+            stats: Stats::new(&sample(&|| {
+                // a failed part is timed all the same
+                let _ = parsed.solve(part);
+            })),
+        });
+    }
+    Ok(results)
+}
+
+/// Medians of a previous run, saved as `dayN phase nanoseconds` lines.
+#[derive(Debug, Default, PartialEq)]
+pub struct Baseline {
+    medians: HashMap<(u8, Phase), Duration>,
+}
+
+impl Baseline {
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut medians = HashMap::new();
+        for line in input.lines().filter(|line| !line.trim().is_empty()) {
+            let mut fields = line.split_whitespace();
+            let day = fields.next().unwrap();
+            let day = day
+                .strip_prefix("day")
+                .and_then(|day| day.parse().ok())
+                .ok_or_else(|| ParseError::at(input, day, "expected a day like day8"))?;
+            let phase = next_token(&mut fields, line, "expected a phase")
+                .map_err(|error| error.within(input, line))?;
+            let phase = Phase::from_name(phase)
+                .ok_or_else(|| ParseError::at(input, phase, "expected parse, part1 or part2"))?;
+            let median = next_token(&mut fields, line, "expected a duration")
+                .map_err(|error| error.within(input, line))?;
+            let median = parse_token(input, median, "expected nanoseconds")?;
+            medians.insert((day, phase), Duration::from_nanos(median));
+        }
+        Ok(Self { medians })
+    }
+
+    pub fn from_results(results: &[BenchResult]) -> Self {
+        Self {
+            medians: results
+                .iter()
+                .map(|result| ((result.day, result.phase), result.stats.median))
+                .collect(),
+        }
+    }
+
+    pub fn get(&self, day: u8, phase: Phase) -> Option<Duration> {
+        self.medians.get(&(day, phase)).copied()
+    }
+
+    /// Relative change of the median compared to the baseline, `0.1` meaning 10% slower.
+    pub fn change(&self, result: &BenchResult) -> Option<f64> {
+        self.get(result.day, result.phase).map(|baseline| {
+            result.stats.median.as_secs_f64() / baseline.as_secs_f64().max(1e-9) - 1.0
+        })
+    }
+}
+
+impl Display for Baseline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut medians = self.medians.iter().collect::<Vec<_>>();
+        medians.sort_unstable_by_key(|((day, phase), _)| (*day, phase.to_string()));
+        for ((day, phase), median) in medians {
+            writeln!(f, "day{} {} {}", day, phase, median.as_nanos())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution;
+
+    fn micros(values: &[u64]) -> Vec<Duration> {
+        values.iter().copied().map(Duration::from_micros).collect()
+    }
+
+    #[test]
+    fn test_stats() {
+        let stats = Stats::new(&micros(&[5, 1, 4, 2, 3]));
+        assert_eq!(stats.min, Duration::from_micros(1));
+        assert_eq!(stats.median, Duration::from_micros(3));
+        assert_eq!(stats.p95, Duration::from_micros(5));
+        assert_eq!(stats.stddev.as_nanos(), 1414);
+
+        let stats = Stats::new(&micros(&[4, 1, 3, 2]));
+        assert_eq!(
+            stats.median,
+            Duration::from_micros(2) + Duration::from_nanos(500)
+        );
+    }
+
+    #[test]
+    fn test_bench() {
+        let puzzle = solution::get(13).unwrap();
+        let results = bench(puzzle, "939\n7,13,x,x,59,x,31,19", 3).unwrap();
+        let phases = results
+            .iter()
+            .map(|result| result.phase)
+            .collect::<Vec<_>>();
+        assert_eq!(phases, vec![Phase::Parse, Phase::Part(1), Phase::Part(2)]);
+        assert!(bench(puzzle, "939", 3).is_err());
+    }
+
+    #[test]
+    fn test_baseline() {
+        let baseline = Baseline::parse("day8 parse 1000\nday8 part1 2000\n").unwrap();
+        assert_eq!(baseline.to_string(), "day8 parse 1000\nday8 part1 2000\n");
+        let result = BenchResult {
+            day: 8,
+            phase: Phase::Part(1),
+            stats: Stats::new(&micros(&[3])),
+        };
+        assert!((baseline.change(&result).unwrap() - 0.5).abs() < 1e-9);
+        assert_eq!(
+            Baseline::parse("day8 part3x 1000"),
+            Err(ParseError::new(
+                1,
+                6,
+                "part3x",
+                "expected parse, part1 or part2"
+            ))
+        );
+    }
+}
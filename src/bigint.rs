@@ -0,0 +1,385 @@
+use std::{
+    cmp::Ordering,
+    convert::TryFrom,
+    fmt::Display,
+    ops::{Add, Mul, Neg, Sub},
+};
+
+/// Arbitrary precision signed integer, for the computations overflowing the primitive types.
+///
+/// The magnitude is stored in base 2^32, least significant digit first and without leading
+/// zeros, so zero is an empty magnitude and is never negative.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: Vec<u32>,
+}
+
+impl BigInt {
+    fn new(negative: bool, mut magnitude: Vec<u32>) -> Self {
+        while magnitude.last() == Some(&0) {
+            magnitude.pop();
+        }
+        Self {
+            negative: negative && !magnitude.is_empty(),
+            magnitude,
+        }
+    }
+
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// Number of bits of the magnitude, 0 for zero.
+    pub fn bits(&self) -> u64 {
+        self.magnitude.last().map_or(0, |&digit| {
+            self.magnitude.len() as u64 * 32 - digit.leading_zeros() as u64
+        })
+    }
+
+    /// The value if it fits in an `i128`.
+    pub fn to_i128(&self) -> Option<i128> {
+        if self.magnitude.len() > 4 {
+            return None;
+        }
+        let magnitude = self
+            .magnitude
+            .iter()
+            .rev()
+            .fold(0u128, |acc, &digit| acc << 32 | digit as u128);
+        if self.negative {
+            0i128.checked_sub_unsigned(magnitude)
+        } else {
+            i128::try_from(magnitude).ok()
+        }
+    }
+
+    /// Quotient truncated toward zero, `None` when dividing by zero.
+    pub fn checked_div(&self, rhs: &Self) -> Option<Self> {
+        let (quotient, _) = div_rem_magnitude(&self.magnitude, &rhs.magnitude)?;
+        Some(Self::new(self.negative != rhs.negative, quotient))
+    }
+
+    /// Remainder of the truncated division, with the sign of `self`.
+    pub fn checked_rem(&self, rhs: &Self) -> Option<Self> {
+        let (_, remainder) = div_rem_magnitude(&self.magnitude, &rhs.magnitude)?;
+        Some(Self::new(self.negative, remainder))
+    }
+
+    pub fn pow(&self, mut exponent: u32) -> Self {
+        let mut base = self.clone();
+        let mut result = Self::from(1i64);
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &result * &base;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+}
+
+fn compare_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0u64;
+    for index in 0..a.len().max(b.len()) {
+        let sum = carry
+            + a.get(index).copied().unwrap_or(0) as u64
+            + b.get(index).copied().unwrap_or(0) as u64;
+        result.push(sum as u32);
+        carry = sum >> 32;
+    }
+    result.push(carry as u32);
+    result
+}
+
+/// `a - b`, `a` must not be less than `b`.
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (index, &digit) in a.iter().enumerate() {
+        let mut difference = digit as i64 - b.get(index).copied().unwrap_or(0) as i64 - borrow;
+        borrow = 0;
+        if difference < 0 {
+            difference += 1 << 32;
+            borrow = 1;
+        }
+        result.push(difference as u32);
+    }
+    result
+}
+
+fn mul_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let product = x as u64 * y as u64 + result[i + j] as u64 + carry;
+            result[i + j] = product as u32;
+            carry = product >> 32;
+        }
+        result[i + b.len()] = carry as u32;
+    }
+    result
+}
+
+/// Divides `a` in place by a single digit from the most significant one, returns the remainder.
+/// `divisor` must not be zero.
+fn div_rem_digit(a: &mut [u32], divisor: u32) -> u32 {
+    let mut remainder = 0u64;
+    for digit in a.iter_mut().rev() {
+        let current = remainder << 32 | *digit as u64;
+        *digit = (current / divisor as u64) as u32;
+        remainder = current % divisor as u64;
+    }
+    remainder as u32
+}
+
+/// Long division one bit at a time, `None` when `b` is zero.
+fn div_rem_magnitude(a: &[u32], b: &[u32]) -> Option<(Vec<u32>, Vec<u32>)> {
+    match *b {
+        [] => return None,
+        [divisor] => {
+            let mut quotient = a.to_vec();
+            let remainder = div_rem_digit(&mut quotient, divisor);
+            return Some((quotient, vec![remainder]));
+        }
+        _ => {}
+    }
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder: Vec<u32> = vec![];
+    for index in (0..a.len() * 32).rev() {
+        // remainder = remainder * 2 + the next bit of a
+        let bit = a[index / 32] >> (index % 32) & 1;
+        let mut carry = bit;
+        for digit in remainder.iter_mut() {
+            let shifted = (*digit as u64) << 1 | carry as u64;
+            *digit = shifted as u32;
+            carry = (shifted >> 32) as u32;
+        }
+        if carry != 0 {
+            remainder.push(carry);
+        }
+        if compare_magnitude(&remainder, b) != Ordering::Less {
+            remainder = sub_magnitude(&remainder, b);
+            while remainder.last() == Some(&0) {
+                remainder.pop();
+            }
+            quotient[index / 32] |= 1 << (index % 32);
+        }
+    }
+    Some((quotient, remainder))
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        Self::from(value as i128)
+    }
+}
+
+impl From<i128> for BigInt {
+    fn from(value: i128) -> Self {
+        let mut magnitude = value.unsigned_abs();
+        let mut digits = vec![];
+        while magnitude > 0 {
+            digits.push(magnitude as u32);
+            magnitude >>= 32;
+        }
+        Self::new(value < 0, digits)
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::new(!self.negative, self.magnitude.clone())
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, rhs: &BigInt) -> BigInt {
+        if self.negative == rhs.negative {
+            return BigInt::new(
+                self.negative,
+                add_magnitude(&self.magnitude, &rhs.magnitude),
+            );
+        }
+        // signs differ : the result has the sign of the larger magnitude
+        match compare_magnitude(&self.magnitude, &rhs.magnitude) {
+            Ordering::Less => {
+                BigInt::new(rhs.negative, sub_magnitude(&rhs.magnitude, &self.magnitude))
+            }
+            _ => BigInt::new(
+                self.negative,
+                sub_magnitude(&self.magnitude, &rhs.magnitude),
+            ),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, rhs: &BigInt) -> BigInt {
+        self + &-rhs
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, rhs: &BigInt) -> BigInt {
+        BigInt::new(
+            self.negative != rhs.negative,
+            mul_magnitude(&self.magnitude, &rhs.magnitude),
+        )
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitude(&self.magnitude, &other.magnitude),
+            (true, true) => compare_magnitude(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for BigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        // groups of 9 decimal digits, least significant first
+        let mut groups = vec![];
+        let mut magnitude = self.magnitude.clone();
+        while !magnitude.is_empty() {
+            groups.push(div_rem_digit(&mut magnitude, 1_000_000_000));
+            while magnitude.last() == Some(&0) {
+                magnitude.pop();
+            }
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", groups.pop().unwrap())?;
+        for group in groups.iter().rev() {
+            write!(f, "{:09}", group)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(value: i128) -> BigInt {
+        BigInt::from(value)
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let values = [
+            0,
+            1,
+            -1,
+            7,
+            -12,
+            u32::MAX as i128,
+            1 << 40,
+            -(1 << 63),
+            i64::MAX as i128 * 3,
+        ];
+        for &a in &values {
+            for &b in &values {
+                assert_eq!((&big(a) + &big(b)).to_i128(), Some(a + b), "{} + {}", a, b);
+                assert_eq!((&big(a) - &big(b)).to_i128(), Some(a - b), "{} - {}", a, b);
+                assert_eq!(
+                    (&big(a) * &big(b)).to_i128(),
+                    a.checked_mul(b),
+                    "{} * {}",
+                    a,
+                    b
+                );
+                assert_eq!(
+                    big(a)
+                        .checked_div(&big(b))
+                        .map(|value| value.to_i128().unwrap()),
+                    a.checked_div(b),
+                    "{} / {}",
+                    a,
+                    b
+                );
+                assert_eq!(
+                    big(a)
+                        .checked_rem(&big(b))
+                        .map(|value| value.to_i128().unwrap()),
+                    a.checked_rem(b),
+                    "{} % {}",
+                    a,
+                    b
+                );
+                assert_eq!(big(a).cmp(&big(b)), a.cmp(&b));
+            }
+        }
+    }
+
+    #[test]
+    fn test_large() {
+        let value = big(2).pow(200);
+        assert_eq!(value.to_i128(), None);
+        assert_eq!((value.bits(), big(-5).bits(), big(0).bits()), (201, 3, 0));
+        assert_eq!(
+            value.to_string(),
+            "1606938044258990275541962092341162602522202993782792835301376"
+        );
+        assert_eq!((-&value).to_string().len(), 62);
+        assert_eq!(value.checked_div(&big(2).pow(190)).unwrap(), big(1024));
+        assert_eq!(big(-3).pow(3), big(-27));
+        assert_eq!(big(i128::MIN).to_i128(), Some(i128::MIN));
+        assert_eq!(big(-1_000_000_000).to_string(), "-1000000000");
+        assert_eq!(big(0).to_string(), "0");
+        assert_eq!(
+            big(-10).pow(4001).to_string(),
+            format!("-1{}", "0".repeat(4001))
+        );
+        assert_eq!(
+            big(7).pow(300).checked_rem(&big(1_000_000_007)).unwrap(),
+            big(7)
+                .pow(300)
+                .checked_rem(&big(1_000_000_007 << 32))
+                .unwrap()
+                .checked_rem(&big(1_000_000_007))
+                .unwrap()
+        );
+    }
+}
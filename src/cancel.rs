@@ -0,0 +1,69 @@
+use crate::error::SolveError;
+use std::{
+    cell::RefCell,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+thread_local! {
+    static CURRENT: RefCell<Option<CancelFlag>> = const { RefCell::new(None) };
+}
+
+/// Flag shared between the runner and the thread solving a day, long running loops call
+/// `checkpoint` so they can be stopped once the flag is raised.
+#[derive(Clone, Debug, Default)]
+pub struct CancelFlag(Arc<AtomicBool>);
+
+impl CancelFlag {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Makes this flag the one checked by `checkpoint` on the current thread.
+    pub fn install(&self) {
+        CURRENT.with(|current| *current.borrow_mut() = Some(self.clone()));
+    }
+}
+
+/// Fails with `SolveError::Cancelled` once the flag installed on the current thread is raised,
+/// always succeeds when there is none.
+pub fn checkpoint() -> Result<(), SolveError> {
+    let cancelled = CURRENT.with(|current| {
+        current
+            .borrow()
+            .as_ref()
+            .is_some_and(CancelFlag::is_cancelled)
+    });
+    if cancelled {
+        Err(SolveError::Cancelled)
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint() {
+        assert_eq!(checkpoint(), Ok(()));
+        let flag = CancelFlag::new();
+        flag.install();
+        assert_eq!(checkpoint(), Ok(()));
+        flag.clone().cancel();
+        assert_eq!(checkpoint(), Err(SolveError::Cancelled));
+        // other threads are not affected
+        assert_eq!(std::thread::spawn(checkpoint).join().unwrap(), Ok(()));
+    }
+}
@@ -0,0 +1,733 @@
+use crate::format::Format;
+use aoc2020::{day20::RenderOptions, input::InputSource, netpbm::ImageFormat, solution};
+use std::{fmt::Display, path::PathBuf, time::Duration};
+
+pub const USAGE: &str = "usage: aoc2020 <command> [options]
+
+commands:
+    run [DAYS...] [--part N] [--input PATH] [--format FORMAT] [--jobs N] [--timeout SECONDS]
+                                run the given days (all days when none is given), on N
+                                threads with --jobs
+    verify [DAYS...] [--input PATH] [--answers FILE] [--timeout SECONDS]
+                                check the answers of the given days against the answers file
+    bench [DAYS...] [--input PATH] [--iterations N] [--save FILE] [--baseline FILE]
+          [--threshold PERCENT]
+                                time the parsing and every part of the given days N times
+                                (10 by default), save the medians or compare them to a saved
+                                baseline, slowdowns over PERCENT (10 by default) are regressions
+    debug 8 [--input PATH]      step through the program of day 8, type help for the commands
+    disasm 8 [--input PATH] [--dot]
+                                list the program of day 8 in basic blocks with its jumps,
+                                loops and unreachable code, or print its graph for Graphviz
+    search PATTERN [--input PATH]
+                                search the image assembled on day 20 for the pattern of the
+                                PATTERN file, `#` being the required pixels, in every
+                                orientation, print the matches and the roughness left
+    export DIR [--input PATH] [--format IMAGE] [--scale N] [--seams] [--ids]
+                                write the tiles of day 20, the assembled picture scaled N
+                                times (1 by default) with lines between the tiles and their
+                                ids, and the image with its sea monsters highlighted to DIR,
+                                as pbm, pgm or ppm (default) files
+    list                        list the available days
+    help                        print this message
+
+DAYS can be single days (8) or inclusive ranges (11..14).
+
+PATH can be a directory containing dayN files, a file when running a single day or - to
+read the input from stdin (`aoc2020 run 7 -` is a shortcut for `--input -`).
+Inputs are read from the AOC2020_INPUTS directory if set, from the crate inputs directory
+otherwise.
+
+FILE defaults to AOC2020_ANSWERS if set, to the answers.toml file of the crate otherwise.
+
+SECONDS is the time given to every day, the parts not solved by then are reported as timed
+out instead of hanging.
+
+FORMAT is text (default), json or csv : json and csv report the answer, its type, the
+parse time and the solve time of every part.
+
+exit codes:
+    0    success
+    1    a day could not be run (missing input, parse error...) a part failed or timed
+         out, an answer is wrong or a benchmark regressed
+    2    invalid command line";
+
+pub const FIRST_DAY: u8 = 1;
+pub const LAST_DAY: u8 = 25;
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Run {
+        days: Vec<u8>,
+        part: Option<u8>,
+        input: InputSource,
+        format: Format,
+        /// Number of days run at the same time.
+        jobs: usize,
+        timeout: Option<Duration>,
+    },
+    Verify {
+        days: Vec<u8>,
+        input: InputSource,
+        answers: Option<PathBuf>,
+        timeout: Option<Duration>,
+    },
+    Bench {
+        days: Vec<u8>,
+        input: InputSource,
+        iterations: usize,
+        save: Option<PathBuf>,
+        baseline: Option<PathBuf>,
+        threshold: f64,
+    },
+    Debug {
+        day: u8,
+        input: InputSource,
+    },
+    Disasm {
+        day: u8,
+        input: InputSource,
+        dot: bool,
+    },
+    Search {
+        pattern: PathBuf,
+        input: InputSource,
+    },
+    Export {
+        directory: PathBuf,
+        input: InputSource,
+        format: ImageFormat,
+        options: RenderOptions,
+    },
+    List,
+    Help,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct UsageError(String);
+
+impl Display for UsageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+pub fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Command, UsageError> {
+    match args.next().as_deref() {
+        Some("run") => parse_run(args),
+        Some("verify") => parse_verify(args),
+        Some("bench") => parse_bench(args),
+        Some("debug") => parse_debug(args),
+        Some("disasm") => parse_disasm(args),
+        Some("search") => parse_search(args),
+        Some("export") => parse_export(args),
+        Some("list") => expect_end(args, Command::List),
+        Some("help") | Some("-h") | Some("--help") | None => expect_end(args, Command::Help),
+        Some(command) => Err(UsageError(format!("unknown command `{}`", command))),
+    }
+}
+
+fn parse_run<I: Iterator<Item = String>>(mut args: I) -> Result<Command, UsageError> {
+    let mut days = vec![];
+    let mut part = None;
+    let mut input = InputSource::Default;
+    let mut format = Format::Text;
+    let mut jobs = 1;
+    let mut timeout = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" | "-i" => input = InputSource::from_arg(&expect_value(&mut args, "--input")?),
+            "-" => input = InputSource::Stdin,
+            "--part" | "-p" => part = Some(parse_part(&expect_value(&mut args, "--part")?)?),
+            "--format" | "-f" => {
+                let value = expect_value(&mut args, "--format")?;
+                format = Format::from_arg(&value).ok_or_else(|| {
+                    UsageError(format!(
+                        "invalid format `{}`, expected text, json or csv",
+                        value
+                    ))
+                })?;
+            }
+            "--jobs" | "-j" => {
+                let value = expect_value(&mut args, "--jobs")?;
+                jobs = parse_count(&value, "jobs")?;
+            }
+            "--timeout" | "-t" => {
+                timeout = Some(parse_timeout(&expect_value(&mut args, "--timeout")?)?)
+            }
+            _ if arg.starts_with('-') => {
+                return Err(UsageError(format!("unknown option `{}`", arg)));
+            }
+            _ => days.extend(parse_days(&arg)?),
+        }
+    }
+
+    // the days without the part are only skipped when running every day
+    if let Some(part) = part {
+        if let Some(&day) = days
+            .iter()
+            .find(|&&day| solution::get(day).is_some_and(|puzzle| part > puzzle.parts()))
+        {
+            return Err(UsageError(format!("day {} has no part {}", day, part)));
+        }
+    }
+    let days = select_days(days, &input)?;
+    Ok(Command::Run {
+        days,
+        part,
+        input,
+        format,
+        jobs,
+        timeout,
+    })
+}
+
+fn parse_verify<I: Iterator<Item = String>>(mut args: I) -> Result<Command, UsageError> {
+    let mut days = vec![];
+    let mut input = InputSource::Default;
+    let mut answers = None;
+    let mut timeout = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" | "-i" => input = InputSource::from_arg(&expect_value(&mut args, "--input")?),
+            "-" => input = InputSource::Stdin,
+            "--answers" | "-a" => {
+                answers = Some(PathBuf::from(expect_value(&mut args, "--answers")?))
+            }
+            "--timeout" | "-t" => {
+                timeout = Some(parse_timeout(&expect_value(&mut args, "--timeout")?)?)
+            }
+            _ if arg.starts_with('-') => {
+                return Err(UsageError(format!("unknown option `{}`", arg)));
+            }
+            _ => days.extend(parse_days(&arg)?),
+        }
+    }
+
+    let days = select_days(days, &input)?;
+    Ok(Command::Verify {
+        days,
+        input,
+        answers,
+        timeout,
+    })
+}
+
+fn parse_bench<I: Iterator<Item = String>>(mut args: I) -> Result<Command, UsageError> {
+    let mut days = vec![];
+    let mut input = InputSource::Default;
+    let mut iterations = 10;
+    let mut save = None;
+    let mut baseline = None;
+    let mut threshold = 10.0;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" | "-i" => input = InputSource::from_arg(&expect_value(&mut args, "--input")?),
+            "-" => input = InputSource::Stdin,
+            "--iterations" | "-n" => {
+                let value = expect_value(&mut args, "--iterations")?;
+                iterations = parse_count(&value, "iterations")?;
+            }
+            "--save" => save = Some(PathBuf::from(expect_value(&mut args, "--save")?)),
+            "--baseline" => baseline = Some(PathBuf::from(expect_value(&mut args, "--baseline")?)),
+            "--threshold" => {
+                let value = expect_value(&mut args, "--threshold")?;
+                threshold = match value.parse::<f64>() {
+                    Ok(threshold) if threshold >= 0.0 => threshold,
+                    _ => {
+                        return Err(UsageError(format!(
+                            "invalid threshold `{}`, expected a percentage",
+                            value
+                        )))
+                    }
+                };
+            }
+            _ if arg.starts_with('-') => {
+                return Err(UsageError(format!("unknown option `{}`", arg)));
+            }
+            _ => days.extend(parse_days(&arg)?),
+        }
+    }
+
+    let days = select_days(days, &input)?;
+    Ok(Command::Bench {
+        days,
+        input,
+        iterations,
+        save,
+        baseline,
+        threshold,
+    })
+}
+
+fn parse_debug<I: Iterator<Item = String>>(mut args: I) -> Result<Command, UsageError> {
+    let mut day = None;
+    let mut input = InputSource::Default;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" | "-i" => input = InputSource::from_arg(&expect_value(&mut args, "--input")?),
+            _ if arg.starts_with('-') => {
+                return Err(UsageError(format!("unknown option `{}`", arg)));
+            }
+            _ => day = Some(parse_program_day(day, &arg)?),
+        }
+    }
+
+    let day = expect_program_day(day)?;
+    if input == InputSource::Stdin {
+        return Err(UsageError(
+            "the debugger reads its commands from stdin, give the input as a file".to_owned(),
+        ));
+    }
+    Ok(Command::Debug { day, input })
+}
+
+fn parse_disasm<I: Iterator<Item = String>>(mut args: I) -> Result<Command, UsageError> {
+    let mut day = None;
+    let mut input = InputSource::Default;
+    let mut dot = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" | "-i" => input = InputSource::from_arg(&expect_value(&mut args, "--input")?),
+            "-" => input = InputSource::Stdin,
+            "--dot" => dot = true,
+            _ if arg.starts_with('-') => {
+                return Err(UsageError(format!("unknown option `{}`", arg)));
+            }
+            _ => day = Some(parse_program_day(day, &arg)?),
+        }
+    }
+
+    let day = expect_program_day(day)?;
+    Ok(Command::Disasm { day, input, dot })
+}
+
+fn parse_search<I: Iterator<Item = String>>(mut args: I) -> Result<Command, UsageError> {
+    let mut pattern = None;
+    let mut input = InputSource::Default;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" | "-i" => input = InputSource::from_arg(&expect_value(&mut args, "--input")?),
+            "-" => input = InputSource::Stdin,
+            _ if arg.starts_with('-') => {
+                return Err(UsageError(format!("unknown option `{}`", arg)));
+            }
+            _ if pattern.is_some() => {
+                return Err(UsageError(format!("unexpected argument `{}`", arg)));
+            }
+            _ => pattern = Some(PathBuf::from(arg)),
+        }
+    }
+
+    let pattern = pattern.ok_or_else(|| UsageError("missing the pattern file".to_owned()))?;
+    Ok(Command::Search { pattern, input })
+}
+
+fn parse_export<I: Iterator<Item = String>>(mut args: I) -> Result<Command, UsageError> {
+    let mut directory = None;
+    let mut input = InputSource::Default;
+    let mut format = ImageFormat::Ppm;
+    let mut options = RenderOptions::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" | "-i" => input = InputSource::from_arg(&expect_value(&mut args, "--input")?),
+            "-" => input = InputSource::Stdin,
+            "--format" | "-f" => {
+                let value = expect_value(&mut args, "--format")?;
+                format = ImageFormat::from_name(&value).ok_or_else(|| {
+                    UsageError(format!(
+                        "invalid image format `{}`, expected pbm, pgm or ppm",
+                        value
+                    ))
+                })?;
+            }
+            "--scale" => {
+                options.scale = parse_count(&expect_value(&mut args, "--scale")?, "scale")?
+            }
+            "--seams" => options.seams = true,
+            "--ids" => options.ids = true,
+            _ if arg.starts_with('-') => {
+                return Err(UsageError(format!("unknown option `{}`", arg)));
+            }
+            _ if directory.is_some() => {
+                return Err(UsageError(format!("unexpected argument `{}`", arg)));
+            }
+            _ => directory = Some(PathBuf::from(arg)),
+        }
+    }
+
+    let directory =
+        directory.ok_or_else(|| UsageError("missing the output directory".to_owned()))?;
+    Ok(Command::Export {
+        directory,
+        input,
+        format,
+        options,
+    })
+}
+
+/// Only day 8 runs a program on the handheld console, a single day can be given.
+fn parse_program_day(previous: Option<u8>, value: &str) -> Result<u8, UsageError> {
+    if previous.is_some() {
+        return Err(UsageError(format!("unexpected argument `{}`", value)));
+    }
+    match parse_day(value)? {
+        8 => Ok(8),
+        day => Err(UsageError(format!("day {} has no program", day))),
+    }
+}
+
+fn expect_program_day(day: Option<u8>) -> Result<u8, UsageError> {
+    day.ok_or_else(|| UsageError("missing the day of the program".to_owned()))
+}
+
+fn expect_value<I: Iterator<Item = String>>(
+    args: &mut I,
+    option: &str,
+) -> Result<String, UsageError> {
+    args.next()
+        .ok_or_else(|| UsageError(format!("missing value for {}", option)))
+}
+
+/// Every day when none is given, sorted and without duplicates.
+fn select_days(mut days: Vec<u8>, input: &InputSource) -> Result<Vec<u8>, UsageError> {
+    if days.is_empty() {
+        days.extend(FIRST_DAY..=LAST_DAY);
+    }
+    days.sort_unstable();
+    days.dedup();
+    if input.is_single() && days.len() > 1 {
+        return Err(UsageError(
+            "a single input file or stdin can only be used with a single day".to_owned(),
+        ));
+    }
+    Ok(days)
+}
+
+fn expect_end<I: Iterator<Item = String>>(
+    mut args: I,
+    command: Command,
+) -> Result<Command, UsageError> {
+    match args.next() {
+        Some(arg) => Err(UsageError(format!("unexpected argument `{}`", arg))),
+        None => Ok(command),
+    }
+}
+
+fn parse_count(value: &str, name: &str) -> Result<usize, UsageError> {
+    match value.parse() {
+        Ok(count) if count > 0 => Ok(count),
+        _ => Err(UsageError(format!(
+            "invalid {} `{}`, expected a positive number",
+            name, value
+        ))),
+    }
+}
+
+fn parse_timeout(value: &str) -> Result<Duration, UsageError> {
+    match value.parse::<f64>() {
+        Ok(seconds) if seconds > 0.0 && seconds.is_finite() => Ok(Duration::from_secs_f64(seconds)),
+        _ => Err(UsageError(format!(
+            "invalid timeout `{}`, expected a positive number of seconds",
+            value
+        ))),
+    }
+}
+
+fn parse_part(value: &str) -> Result<u8, UsageError> {
+    match value {
+        "1" => Ok(1),
+        "2" => Ok(2),
+        _ => Err(UsageError(format!(
+            "invalid part `{}`, expected 1 or 2",
+            value
+        ))),
+    }
+}
+
+fn parse_day(value: &str) -> Result<u8, UsageError> {
+    match value.parse::<u8>() {
+        Ok(day) if (FIRST_DAY..=LAST_DAY).contains(&day) => Ok(day),
+        _ => Err(UsageError(format!(
+            "invalid day `{}`, expected a number between {} and {}",
+            value, FIRST_DAY, LAST_DAY
+        ))),
+    }
+}
+
+/// Parses a single day (`8`) or an inclusive range of days (`11..14` or `11..=14`).
+fn parse_days(value: &str) -> Result<Vec<u8>, UsageError> {
+    let mut bounds = value.splitn(2, "..");
+    let start = parse_day(bounds.next().unwrap())?;
+    match bounds.next() {
+        Some(end) => {
+            let end = parse_day(end.trim_start_matches('='))?;
+            if end < start {
+                return Err(UsageError(format!("empty day range `{}`", value)));
+            }
+            Ok((start..=end).collect())
+        }
+        None => Ok(vec![start]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Command, UsageError> {
+        parse_args(args.split_whitespace().map(str::to_owned))
+    }
+
+    #[test]
+    fn test_parse_run() {
+        assert_eq!(
+            parse("run 8 11..14 --part 2"),
+            Ok(Command::Run {
+                days: vec![8, 11, 12, 13, 14],
+                part: Some(2),
+                input: InputSource::Default,
+                format: Format::Text,
+                jobs: 1,
+                timeout: None
+            })
+        );
+        assert_eq!(
+            parse("run 3..=4 1 3"),
+            Ok(Command::Run {
+                days: vec![1, 3, 4],
+                part: None,
+                input: InputSource::Default,
+                format: Format::Text,
+                jobs: 1,
+                timeout: None
+            })
+        );
+        assert_eq!(
+            parse("run"),
+            Ok(Command::Run {
+                days: (1..=25).collect(),
+                part: None,
+                input: InputSource::Default,
+                format: Format::Text,
+                jobs: 1,
+                timeout: None
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_run_input() {
+        assert_eq!(
+            parse("run 7 --input other/day7.txt"),
+            Ok(Command::Run {
+                days: vec![7],
+                part: None,
+                input: InputSource::File("other/day7.txt".into()),
+                format: Format::Text,
+                jobs: 1,
+                timeout: None
+            })
+        );
+        assert_eq!(
+            parse("run 7 -"),
+            Ok(Command::Run {
+                days: vec![7],
+                part: None,
+                input: InputSource::Stdin,
+                format: Format::Text,
+                jobs: 1,
+                timeout: None
+            })
+        );
+        assert!(parse("run 7 8 -").is_err());
+        assert!(parse("run --input other/day7.txt").is_err());
+        assert!(parse("run 7 --input").is_err());
+    }
+
+    #[test]
+    fn test_parse_run_options() {
+        assert_eq!(
+            parse("run 1 --format csv"),
+            Ok(Command::Run {
+                days: vec![1],
+                part: None,
+                input: InputSource::Default,
+                format: Format::Csv,
+                jobs: 1,
+                timeout: None
+            })
+        );
+        assert!(parse("run 1 --format xml").is_err());
+        assert_eq!(
+            parse("run -j 4"),
+            Ok(Command::Run {
+                days: (1..=25).collect(),
+                part: None,
+                input: InputSource::Default,
+                format: Format::Text,
+                jobs: 4,
+                timeout: None
+            })
+        );
+        assert!(parse("run --jobs 0").is_err());
+        assert_eq!(
+            parse("run 15 --timeout 0.5"),
+            Ok(Command::Run {
+                days: vec![15],
+                part: None,
+                input: InputSource::Default,
+                format: Format::Text,
+                jobs: 1,
+                timeout: Some(Duration::from_millis(500))
+            })
+        );
+        assert!(parse("run --timeout 0").is_err());
+        assert!(parse("run --timeout soon").is_err());
+        assert!(parse("run 1 --format").is_err());
+    }
+
+    #[test]
+    fn test_parse_verify() {
+        assert_eq!(
+            parse("verify 8 --answers other.toml"),
+            Ok(Command::Verify {
+                days: vec![8],
+                input: InputSource::Default,
+                answers: Some(PathBuf::from("other.toml")),
+                timeout: None
+            })
+        );
+        assert_eq!(
+            parse("verify"),
+            Ok(Command::Verify {
+                days: (1..=25).collect(),
+                input: InputSource::Default,
+                answers: None,
+                timeout: None
+            })
+        );
+        assert_eq!(
+            parse("verify 8 -t 2"),
+            Ok(Command::Verify {
+                days: vec![8],
+                input: InputSource::Default,
+                answers: None,
+                timeout: Some(Duration::from_secs(2))
+            })
+        );
+        assert!(parse("verify 8 --part 1").is_err());
+        assert!(parse("verify 8 --answers").is_err());
+    }
+
+    #[test]
+    fn test_parse_bench() {
+        assert_eq!(
+            parse("bench 15 -n 3 --baseline base.txt --threshold 5"),
+            Ok(Command::Bench {
+                days: vec![15],
+                input: InputSource::Default,
+                iterations: 3,
+                save: None,
+                baseline: Some(PathBuf::from("base.txt")),
+                threshold: 5.0
+            })
+        );
+        assert!(parse("bench -n 0").is_err());
+        assert!(parse("bench --threshold -1").is_err());
+    }
+
+    #[test]
+    fn test_parse_debug() {
+        assert_eq!(
+            parse("debug 8"),
+            Ok(Command::Debug {
+                day: 8,
+                input: InputSource::Default
+            })
+        );
+        assert!(parse("debug").is_err());
+        assert!(parse("debug 7").is_err());
+        assert!(parse("debug 8 8").is_err());
+        assert!(parse("debug 8 --input -").is_err());
+    }
+
+    #[test]
+    fn test_parse_disasm() {
+        assert_eq!(
+            parse("disasm 8 --dot"),
+            Ok(Command::Disasm {
+                day: 8,
+                input: InputSource::Default,
+                dot: true
+            })
+        );
+        assert_eq!(
+            parse("disasm 8 -"),
+            Ok(Command::Disasm {
+                day: 8,
+                input: InputSource::Stdin,
+                dot: false
+            })
+        );
+        assert!(parse("disasm 9").is_err());
+        assert!(parse("disasm --dot").is_err());
+    }
+
+    #[test]
+    fn test_parse_search() {
+        assert_eq!(
+            parse("search monster.txt -i picture.txt"),
+            Ok(Command::Search {
+                pattern: PathBuf::from("monster.txt"),
+                input: InputSource::from_arg("picture.txt")
+            })
+        );
+        assert!(parse("search").is_err());
+        assert!(parse("search a.txt b.txt").is_err());
+    }
+
+    #[test]
+    fn test_parse_export() {
+        assert_eq!(
+            parse("export images --format pgm --scale 4 --ids"),
+            Ok(Command::Export {
+                directory: PathBuf::from("images"),
+                input: InputSource::Default,
+                format: ImageFormat::Pgm,
+                options: RenderOptions {
+                    scale: 4,
+                    seams: false,
+                    ids: true
+                }
+            })
+        );
+        assert!(parse("export").is_err());
+        assert!(parse("export images --format png").is_err());
+        assert!(parse("export images --scale 0").is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("run 26").is_err());
+        assert!(parse("run 0").is_err());
+        assert!(parse("run 14..11").is_err());
+        assert!(parse("run 1 --part 3").is_err());
+        assert_eq!(
+            parse("run 24..25 --part 2"),
+            Err(UsageError("day 25 has no part 2".to_owned()))
+        );
+        assert!(parse("run 25 --part 1").is_ok());
+        assert!(parse("run --part 2").is_ok());
+        assert!(parse("run 1 --part").is_err());
+        assert!(parse("list 1").is_err());
+        assert!(parse("frobnicate").is_err());
+    }
+
+    #[test]
+    fn test_parse_list_and_help() {
+        assert_eq!(parse("list"), Ok(Command::List));
+        assert_eq!(parse(""), Ok(Command::Help));
+        assert_eq!(parse("--help"), Ok(Command::Help));
+    }
+}
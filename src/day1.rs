@@ -1,41 +1,97 @@
-pub fn run() {
-    let timer = std::time::Instant::now();
-    let contents = std::fs::read_to_string("inputs/day1").unwrap();
-    let numbers = contents.lines().map(|s| s.parse::<u32>().unwrap());
+use crate::{
+    error::{parse_token, ParseError, SolveError},
+    solution::Solution,
+};
+use std::collections::{HashMap, HashSet};
 
-    let mut first_set = std::collections::HashSet::new();
-    let mut second_map = std::collections::HashMap::new();
-    let mut seen_numbers = vec![];
-    let mut first_found = false;
-    let mut second_found = false;
-    for number in numbers {
+pub fn parse_input(input: &str) -> Result<Vec<u32>, ParseError> {
+    input
+        .lines()
+        .map(|line| parse_token(input, line, "expected a number"))
+        .collect()
+}
+
+/// Numbers larger than 2020 cannot be part of a sum.
+fn entries(numbers: &[u32]) -> impl Iterator<Item = u32> + '_ {
+    numbers.iter().copied().filter(|&number| number <= 2020)
+}
+
+pub fn solve_1(numbers: &[u32]) -> Result<u32, SolveError> {
+    let mut seen_numbers = HashSet::new();
+    for number in entries(numbers) {
         let comp = 2020 - number;
-        if first_set.contains(&comp) {
-            println!(
-                "day 1 solution 1 : {}, {}us",
-                number * comp,
-                timer.elapsed().as_micros()
-            );
-            first_found = true;
+        if seen_numbers.contains(&comp) {
+            return Ok(number * comp);
         }
+        seen_numbers.insert(number);
+    }
+    Err(SolveError::NoSolution(
+        "no two entries sum to 2020".to_owned(),
+    ))
+}
 
-        if second_map.contains_key(&comp) {
-            println!(
-                "day 1 solution 2 : {}, {}us",
-                number * second_map.get(&comp).unwrap(),
-                timer.elapsed().as_micros()
-            );
-            second_found = true;
+pub fn solve_2(numbers: &[u32]) -> Result<u32, SolveError> {
+    // sum of each pair of seen numbers mapped to their product
+    let mut pairs = HashMap::new();
+    let mut seen_numbers = vec![];
+    for number in entries(numbers) {
+        let comp = 2020 - number;
+        if let Some(product) = pairs.get(&comp) {
+            return Ok(number * product);
         }
-
-        first_set.insert(number);
         for seen_number in &seen_numbers {
-            second_map.insert(seen_number + number, seen_number * number);
+            pairs.insert(seen_number + number, seen_number * number);
         }
         seen_numbers.push(number);
+    }
+    Err(SolveError::NoSolution(
+        "no three entries sum to 2020".to_owned(),
+    ))
+}
 
-        if first_found && second_found {
-            break;
-        }
+pub struct Day1;
+
+impl Solution for Day1 {
+    type Input<'a> = Vec<u32>;
+    type Output1 = Result<u32, SolveError>;
+    type Output2 = Result<u32, SolveError>;
+
+    const DAY: u8 = 1;
+    const TITLE: &'static str = "Report Repair";
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(numbers: &Self::Input<'_>) -> Result<u32, SolveError> {
+        solve_1(numbers)
+    }
+
+    fn part_2(numbers: &Self::Input<'_>) -> Result<u32, SolveError> {
+        solve_2(numbers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve_1() {
+        let input = "1721\n979\n366\n299\n675\n1456";
+        assert_eq!(solve_1(&parse_input(input).unwrap()), Ok(514579));
+    }
+
+    #[test]
+    fn test_solve_2() {
+        let input = "1721\n979\n366\n299\n675\n1456";
+        assert_eq!(solve_2(&parse_input(input).unwrap()), Ok(241861950));
+    }
+
+    #[test]
+    fn test_no_solution() {
+        let numbers = parse_input("1010\n3000\n4294967295\n1").unwrap();
+        assert!(matches!(solve_1(&numbers), Err(SolveError::NoSolution(_))));
+        assert!(matches!(solve_2(&numbers), Err(SolveError::NoSolution(_))));
     }
 }
@@ -1,4 +1,9 @@
-fn solve_1(sorted_values: &Vec<u8>) -> u32 {
+use crate::{
+    error::{parse_token, ParseError},
+    solution::Solution,
+};
+
+pub fn solve_1(sorted_values: &[u8]) -> u32 {
     let mut diff_1 = 0;
     let mut diff_3 = 0;
     for i in 0..(sorted_values.len() - 1) {
@@ -25,7 +30,7 @@ Avoid recursion
 16 -> from 15 -> 8
 19 -> from 16 -> 8
 */
-fn solve_2(sorted_values: &Vec<u8>) -> u64 {
+pub fn solve_2(sorted_values: &[u8]) -> u64 {
     let mut paths = vec![0; sorted_values.len()];
     paths[0] = 1;
     for i in 0..sorted_values.len() - 1 {
@@ -38,27 +43,37 @@ fn solve_2(sorted_values: &Vec<u8>) -> u64 {
     paths[paths.len() - 1]
 }
 
-fn parse_input(input: &str) -> Vec<u8> {
-    let mut vec: Vec<u8> = input.lines().map(|s| s.parse().unwrap()).collect();
+pub fn parse_input(input: &str) -> Result<Vec<u8>, ParseError> {
+    let mut vec = input
+        .lines()
+        .map(|line| parse_token(input, line, "expected a joltage between 0 and 255"))
+        .collect::<Result<Vec<u8>, _>>()?;
     vec.push(0);
     vec.sort();
-    vec
+    Ok(vec)
 }
 
-pub fn run() {
-    let timer = std::time::Instant::now();
-    let input = std::fs::read_to_string("inputs/day10").unwrap();
-    let sorted_values = parse_input(&input);
-    println!(
-        "day 10 solution 1 : {}, {}us",
-        solve_1(&sorted_values),
-        timer.elapsed().as_micros()
-    );
-    println!(
-        "day 10 solution 2 : {}, {}us",
-        solve_2(&sorted_values),
-        timer.elapsed().as_micros()
-    );
+pub struct Day10;
+
+impl Solution for Day10 {
+    type Input<'a> = Vec<u8>;
+    type Output1 = u32;
+    type Output2 = u64;
+
+    const DAY: u8 = 10;
+    const TITLE: &'static str = "Adapter Array";
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(sorted_values: &Self::Input<'_>) -> u32 {
+        solve_1(sorted_values)
+    }
+
+    fn part_2(sorted_values: &Self::Input<'_>) -> u64 {
+        solve_2(sorted_values)
+    }
 }
 
 #[cfg(test)]
@@ -68,21 +83,21 @@ mod tests {
     #[test]
     fn test_solution_1() {
         let input = "28\n33\n18\n42\n31\n14\n46\n20\n48\n47\n24\n23\n49\n45\n19\n38\n39\n11\n1\n32\n25\n35\n8\n17\n7\n9\n4\n2\n34\n10\n3";
-        let values = parse_input(&input);
+        let values = parse_input(input).unwrap();
         assert_eq!(solve_1(&values), 220);
     }
 
     #[test]
     fn test_solution_2() {
         let input = "16\n10\n15\n5\n1\n11\n7\n19\n6\n12\n4";
-        let mut values = parse_input(&input);
-        assert_eq!(solve_2(&mut values), 8);
+        let values = parse_input(input).unwrap();
+        assert_eq!(solve_2(&values), 8);
     }
 
     #[test]
     fn test_solution_2_2() {
         let input = "28\n33\n18\n42\n31\n14\n46\n20\n48\n47\n24\n23\n49\n45\n19\n38\n39\n11\n1\n32\n25\n35\n8\n17\n7\n9\n4\n2\n34\n10\n3";
-        let mut values = parse_input(&input);
-        assert_eq!(solve_2(&mut values), 19208);
+        let values = parse_input(input).unwrap();
+        assert_eq!(solve_2(&values), 19208);
     }
 }
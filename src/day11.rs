@@ -1,3 +1,4 @@
+use crate::{error::ParseError, solution::Solution};
 use std::{fmt::Display, ops::RangeInclusive};
 
 #[derive(Clone, PartialEq)]
@@ -39,31 +40,50 @@ type SeatRow = Vec<SeatState>;
 type SeatLayout = Vec<SeatRow>;
 
 #[derive(Clone)]
-struct Layout {
+pub struct Layout {
     layout: SeatLayout,
 }
 
 impl Layout {
-    fn new(input: &str) -> Self {
+    pub fn new(input: &str) -> Result<Self, ParseError> {
         let mut layout = Vec::with_capacity(input.lines().count());
         for line in input.lines() {
             let mut row = Vec::with_capacity(line.len());
-            for c in line.chars() {
+            for (index, c) in line.char_indices() {
                 let seat = match c {
                     'L' => SeatState::Empty,
                     '#' => SeatState::Occupied,
                     '.' => SeatState::NoSeat,
-                    _ => panic!("invalid char in layout : {}", c),
+                    _ => {
+                        return Err(ParseError::at(
+                            input,
+                            &line[index..index + c.len_utf8()],
+                            "invalid char in layout, expected L, # or .",
+                        ))
+                    }
                 };
                 row.push(seat);
             }
+            if layout
+                .first()
+                .is_some_and(|first: &SeatRow| first.len() != row.len())
+            {
+                return Err(ParseError::at(
+                    input,
+                    line,
+                    "every row of the layout should have the same length",
+                ));
+            }
             layout.push(row);
         }
 
-        Self { layout }
+        if layout.is_empty() || layout[0].is_empty() {
+            return Err(ParseError::at(input, input, "empty layout"));
+        }
+        Ok(Self { layout })
     }
 
-    fn next_1(&mut self) -> bool {
+    pub fn next_1(&mut self) -> bool {
         let row_count = self.layout.len();
         let column_count = self.layout[0].len();
         let mut new_layout = Vec::with_capacity(row_count);
@@ -120,7 +140,7 @@ impl Layout {
         mutated
     }
 
-    fn next_2(&mut self) -> bool {
+    pub fn next_2(&mut self) -> bool {
         let row_count = self.layout.len();
         let column_count = self.layout[0].len();
         let mut new_layout = Vec::with_capacity(row_count);
@@ -219,7 +239,7 @@ impl Layout {
         }
     }
 
-    fn count_occupied(&self) -> usize {
+    pub fn count_occupied(&self) -> usize {
         self.layout.iter().flatten().fold(0, |acc, seat| {
             if *seat == SeatState::Occupied {
                 acc + 1
@@ -245,23 +265,31 @@ fn get_safe_range(value: usize, min: usize, max: usize) -> RangeInclusive<usize>
     min..=max
 }
 
-pub fn run() {
-    let timer = std::time::Instant::now();
-    let input = std::fs::read_to_string("inputs/day11").unwrap();
-    let mut layout = Layout::new(&input);
-    let mut layout_2 = layout.clone();
-    while layout.next_1() {}
-    println!(
-        "day 11 solution 1 : {}, {}us",
-        layout.count_occupied(),
-        timer.elapsed().as_micros()
-    );
-    while layout_2.next_2() {}
-    println!(
-        "day 11 solution 2 : {}, {}us",
-        layout_2.count_occupied(),
-        timer.elapsed().as_micros()
-    );
+pub struct Day11;
+
+impl Solution for Day11 {
+    type Input<'a> = Layout;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    const DAY: u8 = 11;
+    const TITLE: &'static str = "Seating System";
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        Layout::new(input)
+    }
+
+    fn part_1(layout: &Self::Input<'_>) -> usize {
+        let mut layout = layout.clone();
+        while layout.next_1() {}
+        layout.count_occupied()
+    }
+
+    fn part_2(layout: &Self::Input<'_>) -> usize {
+        let mut layout = layout.clone();
+        while layout.next_2() {}
+        layout.count_occupied()
+    }
 }
 
 #[cfg(test)]
@@ -281,11 +309,30 @@ LLLLLLLLLL
 L.LLLLLL.L
 L.LLLLL.LL";
 
-        let mut layout = Layout::new(&input);
+        let mut layout = Layout::new(input).unwrap();
         while layout.next_1() {}
         assert_eq!(layout.count_occupied(), 37);
     }
 
+    #[test]
+    fn test_parse_error() {
+        let input = "L.LL.LL.LL\nLLLLLLL.LL\nL.L.L..X..";
+        assert_eq!(
+            Layout::new(input).err().unwrap(),
+            ParseError::new(3, 8, "X", "invalid char in layout, expected L, # or .")
+        );
+        let input = "L.LL.LL.LL\nLLLLLLL.L";
+        assert_eq!(
+            Layout::new(input).err().unwrap(),
+            ParseError::new(
+                2,
+                1,
+                "LLLLLLL.L",
+                "every row of the layout should have the same length"
+            )
+        );
+    }
+
     #[test]
     fn test_solution_2() {
         let input = "L.LL.LL.LL
@@ -299,7 +346,7 @@ LLLLLLLLLL
 L.LLLLLL.L
 L.LLLLL.LL";
 
-        let mut layout = Layout::new(&input);
+        let mut layout = Layout::new(input).unwrap();
         while layout.next_2() {}
         assert_eq!(layout.count_occupied(), 26);
     }
@@ -1,5 +1,10 @@
+use crate::{
+    error::{parse_token, ParseError},
+    solution::Solution,
+};
+
 #[derive(Debug)]
-enum Action {
+pub enum Action {
     North(i32),
     South(i32),
     East(i32),
@@ -10,34 +15,52 @@ enum Action {
 }
 
 impl Action {
-    fn new(action: &str) -> Self {
-        match &action[..1] {
-            "N" => Action::North(action[1..].parse().unwrap()),
-            "S" => Action::South(action[1..].parse().unwrap()),
-            "E" => Action::East(action[1..].parse().unwrap()),
-            "W" => Action::West(action[1..].parse().unwrap()),
-            "L" => Action::Left(action[1..].parse().unwrap()),
-            "R" => Action::Right(action[1..].parse().unwrap()),
-            "F" => Action::Forward(action[1..].parse().unwrap()),
-            _ => panic!("invalid action"),
+    pub fn new(action: &str) -> Result<Self, ParseError> {
+        let split = action.chars().next().map_or(0, char::len_utf8);
+        let (name, value) = action.split_at(split);
+        let number = parse_token(action, value, "expected a number")?;
+        let angle = || {
+            if number % 90 == 0 {
+                Ok(number)
+            } else {
+                Err(ParseError::at(
+                    action,
+                    value,
+                    "expected a multiple of 90 degrees",
+                ))
+            }
+        };
+        match name {
+            "N" => Ok(Action::North(number)),
+            "S" => Ok(Action::South(number)),
+            "E" => Ok(Action::East(number)),
+            "W" => Ok(Action::West(number)),
+            "L" => Ok(Action::Left(angle()?)),
+            "R" => Ok(Action::Right(angle()?)),
+            "F" => Ok(Action::Forward(number)),
+            _ => Err(ParseError::at(
+                action,
+                name,
+                "invalid action, expected N, S, E, W, L, R or F",
+            )),
         }
     }
 }
 
-struct Instruction {
+pub struct Instruction {
     actions: Vec<Action>,
 }
 
 impl Instruction {
-    fn new(input: &str) -> Self {
+    pub fn new(input: &str) -> Result<Self, ParseError> {
         let mut actions = vec![];
         for line in input.lines() {
-            actions.push(Action::new(line));
+            actions.push(Action::new(line).map_err(|error| error.within(input, line))?);
         }
-        Self { actions }
+        Ok(Self { actions })
     }
 
-    fn solve_1(&self) -> u32 {
+    pub fn solve_1(&self) -> u32 {
         let mut angle = 0;
         let mut north = 0;
         let mut east = 0;
@@ -70,7 +93,7 @@ impl Instruction {
         (north.abs() + east.abs()) as u32
     }
 
-    fn solve_2(&self) -> u32 {
+    pub fn solve_2(&self) -> u32 {
         let mut waypoint = (10, 1);
         let mut ship = (0, 0);
         for action in &self.actions {
@@ -112,20 +135,27 @@ fn rotate(coordinates: (i32, i32), theta: f32) -> (i32, i32) {
     )
 }
 
-pub fn run() {
-    let timer = std::time::Instant::now();
-    let input = std::fs::read_to_string("inputs/day12").unwrap();
-    let instruction = Instruction::new(&input);
-    println!(
-        "day 12 solution 1 : {}, {}us",
-        instruction.solve_1(),
-        timer.elapsed().as_micros()
-    );
-    println!(
-        "day 12 solution 2 : {}, {}us",
-        instruction.solve_2(),
-        timer.elapsed().as_micros()
-    );
+pub struct Day12;
+
+impl Solution for Day12 {
+    type Input<'a> = Instruction;
+    type Output1 = u32;
+    type Output2 = u32;
+
+    const DAY: u8 = 12;
+    const TITLE: &'static str = "Rain Risk";
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        Instruction::new(input)
+    }
+
+    fn part_1(instruction: &Self::Input<'_>) -> u32 {
+        instruction.solve_1()
+    }
+
+    fn part_2(instruction: &Self::Input<'_>) -> u32 {
+        instruction.solve_2()
+    }
 }
 
 #[cfg(test)]
@@ -135,14 +165,28 @@ mod tests {
     #[test]
     fn test_solution_1() {
         let input = "F10\nN3\nF7\nR90\nF11";
-        let instruction = Instruction::new(&input);
+        let instruction = Instruction::new(input).unwrap();
         assert_eq!(instruction.solve_1(), 25);
     }
 
+    #[test]
+    fn test_parse_error() {
+        let input = "F10\nN3\nX7";
+        assert_eq!(
+            Instruction::new(input).err().unwrap(),
+            ParseError::new(3, 1, "X", "invalid action, expected N, S, E, W, L, R or F")
+        );
+        let input = "F10\nR45";
+        assert_eq!(
+            Instruction::new(input).err().unwrap(),
+            ParseError::new(2, 2, "45", "expected a multiple of 90 degrees")
+        );
+    }
+
     #[test]
     fn test_solution_2() {
         let input = "F10\nN3\nF7\nR90\nF11";
-        let instruction = Instruction::new(&input);
+        let instruction = Instruction::new(input).unwrap();
         assert_eq!(instruction.solve_2(), 286);
     }
 }
@@ -1,28 +1,41 @@
-struct Problem {
+use crate::{
+    error::{next_token, parse_token, ParseError},
+    solution::Solution,
+};
+
+pub struct Problem {
     timestamp: u32,
     bus: Vec<(usize, u32)>,
 }
 
 impl Problem {
-    fn new(input: &str) -> Self {
+    pub fn new(input: &str) -> Result<Self, ParseError> {
         let mut lines = input.lines();
-        let timestamp = lines.next().unwrap().parse().unwrap();
-        let bus =
-            lines
-                .next()
-                .unwrap()
-                .split(',')
-                .enumerate()
-                .fold(vec![], |mut acc, (index, curr)| {
-                    if let Ok(n) = curr.parse::<u32>() {
-                        acc.push((index, n));
-                    }
-                    acc
-                });
-        Self { timestamp, bus }
+        let timestamp = next_token(&mut lines, input, "expected a timestamp")?;
+        let timestamp = parse_token(input, timestamp, "expected a timestamp")?;
+        let mut bus = vec![];
+        for (index, id) in next_token(&mut lines, input, "expected bus ids")?
+            .split(',')
+            .enumerate()
+        {
+            if id != "x" {
+                match id.parse() {
+                    Ok(n) if n > 0 => bus.push((index, n)),
+                    _ => return Err(ParseError::at(input, id, "expected a bus id or x")),
+                }
+            }
+        }
+        if bus.is_empty() {
+            return Err(ParseError::at(
+                input,
+                &input[input.len()..],
+                "expected at least one bus id",
+            ));
+        }
+        Ok(Self { timestamp, bus })
     }
 
-    fn solve_1(&self) -> u32 {
+    pub fn solve_1(&self) -> u32 {
         let mut index = 0;
         let mut min_to_wait =
             (self.bus[index].1 - self.timestamp % self.bus[index].1) % self.bus[index].1;
@@ -47,11 +60,11 @@ impl Problem {
     * once this timestamp is found, timestamps that satisfy the next equations are of form (t0 + n * lcm(schedule0, schedule1))
 
     */
-    fn solve_2(&self) -> u64 {
+    pub fn solve_2(&self) -> u64 {
         let mut incr = self.bus[0].1 as u64;
         let mut timestamp = incr;
         for i in 1..self.bus.len() {
-            while (timestamp as usize + self.bus[i].0) % self.bus[i].1 as usize != 0 {
+            while !(timestamp as usize + self.bus[i].0).is_multiple_of(self.bus[i].1 as usize) {
                 timestamp += incr;
             }
             incr = lcm(incr, self.bus[i].1 as u64);
@@ -77,20 +90,27 @@ fn lcm(a: u64, b: u64) -> u64 {
     a * b / gcd(a, b)
 }
 
-pub fn run() {
-    let timer = std::time::Instant::now();
-    let input = std::fs::read_to_string("inputs/day13").unwrap();
-    let problem = Problem::new(&input);
-    println!(
-        "day 13 solution 1 : {}, {}us",
-        problem.solve_1(),
-        timer.elapsed().as_micros()
-    );
-    println!(
-        "day 13 solution 2 : {}, {}us",
-        problem.solve_2(),
-        timer.elapsed().as_micros()
-    );
+pub struct Day13;
+
+impl Solution for Day13 {
+    type Input<'a> = Problem;
+    type Output1 = u32;
+    type Output2 = u64;
+
+    const DAY: u8 = 13;
+    const TITLE: &'static str = "Shuttle Search";
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        Problem::new(input)
+    }
+
+    fn part_1(problem: &Self::Input<'_>) -> u32 {
+        problem.solve_1()
+    }
+
+    fn part_2(problem: &Self::Input<'_>) -> u64 {
+        problem.solve_2()
+    }
 }
 
 #[cfg(test)]
@@ -100,14 +120,23 @@ mod tests {
     #[test]
     fn test_solution_1() {
         let input = "939\n7,13,x,x,59,x,31,19";
-        let problem = Problem::new(&input);
+        let problem = Problem::new(input).unwrap();
         assert_eq!(problem.solve_1(), 295);
     }
 
     #[test]
     fn test_solution_2() {
         let input = "939\n7,13,x,x,59,x,31,19";
-        let problem = Problem::new(&input);
+        let problem = Problem::new(input).unwrap();
         assert_eq!(problem.solve_2(), 1068781);
     }
+
+    #[test]
+    fn test_parse_error() {
+        let input = "939\n7,13,x,0,59";
+        assert_eq!(
+            Problem::new(input).err().unwrap(),
+            ParseError::new(2, 8, "0", "expected a bus id or x")
+        );
+    }
 }
@@ -1,124 +1,139 @@
+use crate::{
+    error::{expect_chars, next_token, parse_token, ParseError},
+    solution::Solution,
+};
 use std::collections::HashMap;
 
-fn solve_1(input: &str) -> u64 {
-    let mut memory = HashMap::new();
-    let mut or_mask = 0u64;
-    let mut and_mask = !0u64;
-    for line in input.lines() {
-        let mut split = line.split(" = ");
-        let instruction = split.next().unwrap();
-        match instruction {
-            "mask" => {
-                for (index, c) in split.next().unwrap().char_indices() {
-                    let bit = 1u64 << (35 - index);
-                    match c {
-                        'X' => {
-                            // set the specific bit to 0 for OR and 1 for AND so it is not flipped
-                            or_mask &= !bit;
-                            and_mask |= bit;
-                        }
-                        '1' => {
-                            // set both to 1
-                            or_mask |= bit;
-                            and_mask |= bit;
-                        }
-                        '0' => {
-                            // set both to 0
-                            or_mask &= !bit;
-                            and_mask &= !bit;
-                        }
-                        _ => panic!("invalid mask char"),
-                    }
+/// Bits of a mask : the `1` bits are set, the `X` bits are left unchanged for the values and
+/// floating for the addresses, the others are `0`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mask {
+    pub ones: u64,
+    pub floating: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Instruction {
+    Mask(Mask),
+    Mem { address: u64, value: u64 },
+}
+
+/// Parses every line as either a 36 bits mask or a write to a 36 bits address.
+pub fn parse_input(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    input
+        .lines()
+        .map(|line| {
+            let mut split = line.splitn(2, " = ");
+            let instruction = split.next().unwrap();
+            let value = next_token(&mut split, line, "expected ` = `")
+                .map_err(|error| error.within(input, line))?;
+            if instruction == "mask" {
+                expect_chars(input, value, "X01")?;
+                if value.len() != 36 {
+                    return Err(ParseError::at(input, value, "expected a mask of 36 bits"));
                 }
+                let bits = |bit: u8| {
+                    value
+                        .bytes()
+                        .fold(0, |bits, c| bits << 1 | (c == bit) as u64)
+                };
+                Ok(Instruction::Mask(Mask {
+                    ones: bits(b'1'),
+                    floating: bits(b'X'),
+                }))
+            } else if let Some(address) = instruction
+                .strip_prefix("mem[")
+                .and_then(|s| s.strip_suffix(']'))
+            {
+                let address: u64 = parse_token(input, address, "expected an address")?;
+                if address >> 36 != 0 {
+                    return Err(ParseError::at(
+                        input,
+                        &instruction[4..instruction.len() - 1],
+                        "expected an address of 36 bits",
+                    ));
+                }
+                let value = parse_token(input, value, "expected a value")?;
+                Ok(Instruction::Mem { address, value })
+            } else {
+                Err(ParseError::at(
+                    input,
+                    instruction,
+                    "invalid instruction, expected mask or mem[address]",
+                ))
             }
-            _ => {
-                // should be mem[xxx]
-                let address = instruction[4..instruction.len() - 1]
-                    .parse::<u64>()
-                    .unwrap();
-                let value = split.next().unwrap().parse::<u64>().unwrap();
-                memory.insert(address, value & and_mask | or_mask);
+        })
+        .collect()
+}
+
+/// The mask sets or clears the bits of the values, except the floating ones.
+pub fn solve_1(instructions: &[Instruction]) -> u64 {
+    let mut memory = HashMap::new();
+    let mut mask = Mask {
+        ones: 0,
+        floating: !0,
+    };
+    for &instruction in instructions {
+        match instruction {
+            Instruction::Mask(new_mask) => mask = new_mask,
+            Instruction::Mem { address, value } => {
+                memory.insert(address, value & mask.floating | mask.ones);
             }
         }
     }
 
-    memory.values().fold(0u64, |acc, curr| acc + curr)
+    memory.values().sum()
 }
 
-fn solve_2(input: &str) -> u64 {
+/// The mask sets the bits of the addresses, the floating bits take every value.
+pub fn solve_2(instructions: &[Instruction]) -> u64 {
     let mut memory = HashMap::new();
-    let mut or_mask = 0u64;
-    let mut mask = "";
-    for line in input.lines() {
-        let mut split = line.split(" = ");
-        let instruction = split.next().unwrap();
+    let mut mask = Mask {
+        ones: 0,
+        floating: 0,
+    };
+    for &instruction in instructions {
         match instruction {
-            "mask" => {
-                mask = split.next().unwrap();
-                or_mask =
-                    mask.char_indices()
-                        .filter(|(_, c)| *c == '1')
-                        .fold(0u64, |acc, (index, _)| {
-                            let bit = 1u64 << (35 - index);
-                            acc | bit
-                        });
-            }
-            _ => {
-                // should be mem[xxx]
-                let address = format!(
-                    "{:0>36b}",
-                    &instruction[4..instruction.len() - 1]
-                        .parse::<u64>()
-                        .unwrap()
-                );
-                let value = split.next().unwrap().parse::<u64>().unwrap();
-
-                let floating_bits = mask
-                    .char_indices()
-                    .filter(|(_, c)| *c == 'X')
-                    .collect::<Vec<_>>();
-                if floating_bits.len() > 0 {
-                    // generate all the address and write value
-                    for i in 0..1u64 << floating_bits.len() {
-                        let bin_str = format!("{:0length$b}", i, length = floating_bits.len())
-                            .chars()
-                            .collect::<Vec<_>>();
-                        let mut new_address = String::new();
-                        let mut current_index = 0usize;
-                        for j in 0..floating_bits.len() {
-                            new_address.push_str(&address[current_index..floating_bits[j].0]);
-                            new_address.push(bin_str[j]);
-                            current_index = floating_bits[j].0 + 1;
-                        }
-                        new_address.push_str(&address[current_index..]);
-                        memory.insert(
-                            u64::from_str_radix(&new_address, 2).unwrap() | or_mask,
-                            value,
-                        );
+            Instruction::Mask(new_mask) => mask = new_mask,
+            Instruction::Mem { address, value } => {
+                let address = (address | mask.ones) & !mask.floating;
+                // every subset of the floating bits, from the empty one to all of them
+                let mut floating = 0u64;
+                loop {
+                    memory.insert(address | floating, value);
+                    if floating == mask.floating {
+                        break;
                     }
-                } else {
-                    memory.insert(address.parse::<u64>().unwrap() | or_mask, value);
+                    floating = floating.wrapping_sub(mask.floating) & mask.floating;
                 }
             }
         }
     }
 
-    memory.values().fold(0u64, |acc, curr| acc + curr)
+    memory.values().sum()
 }
 
-pub fn run() {
-    let timer = std::time::Instant::now();
-    let input = std::fs::read_to_string("inputs/day14").unwrap();
-    println!(
-        "day 14 solution 1 : {}, {}us",
-        solve_1(&input),
-        timer.elapsed().as_micros()
-    );
-    println!(
-        "day 14 solution 2 : {}, {}us",
-        solve_2(&input),
-        timer.elapsed().as_micros()
-    );
+pub struct Day14;
+
+impl Solution for Day14 {
+    type Input<'a> = Vec<Instruction>;
+    type Output1 = u64;
+    type Output2 = u64;
+
+    const DAY: u8 = 14;
+    const TITLE: &'static str = "Docking Data";
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(instructions: &Self::Input<'_>) -> u64 {
+        solve_1(instructions)
+    }
+
+    fn part_2(instructions: &Self::Input<'_>) -> u64 {
+        solve_2(instructions)
+    }
 }
 
 #[cfg(test)]
@@ -129,13 +144,50 @@ mod tests {
     fn test_solution_1() {
         let input =
             "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X\nmem[8] = 11\nmem[7] = 101\nmem[8] = 0";
-        assert_eq!(solve_1(&input), 165);
+        assert_eq!(solve_1(&parse_input(input).unwrap()), 165);
     }
 
     #[test]
     fn test_solution_2() {
         let input =
             "mask = 000000000000000000000000000000X1001X\nmem[42] = 100\nmask = 00000000000000000000000000000000X0XX\nmem[26] = 1";
-        assert_eq!(solve_2(&input), 208);
+        assert_eq!(solve_2(&parse_input(input).unwrap()), 208);
+    }
+
+    #[test]
+    fn test_parsing() {
+        let input = "mask = 1XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX0X\nmem[8] = 11";
+        assert_eq!(
+            parse_input(input),
+            Ok(vec![
+                Instruction::Mask(Mask {
+                    ones: 1 << 35,
+                    floating: (1 << 35) - 1 - 2
+                }),
+                Instruction::Mem {
+                    address: 8,
+                    value: 11
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let input = "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X\nmem[8] = 11\nmen[7] = 101";
+        assert_eq!(
+            parse_input(input),
+            Err(ParseError::new(
+                3,
+                1,
+                "men[7]",
+                "invalid instruction, expected mask or mem[address]"
+            ))
+        );
+        let input = "mask = XXXX2";
+        assert_eq!(
+            parse_input(input),
+            Err(ParseError::new(1, 12, "2", "expected one of `X01`"))
+        );
     }
 }
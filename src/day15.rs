@@ -1,10 +1,20 @@
+use crate::{
+    cancel::checkpoint,
+    error::{parse_token, ParseError, SolveError},
+    solution::Solution,
+};
 use std::collections::HashMap;
 
-fn _solve(input: &str, nth: usize) -> u32 {
-    let numbers = input
+/// Parses a non empty list of comma separated numbers.
+pub fn parse_input(input: &str) -> Result<Vec<u32>, ParseError> {
+    input
         .split(',')
-        .map(|s| s.parse::<u32>().unwrap())
-        .collect::<Vec<_>>();
+        .map(|number| parse_token(input, number, "expected a number"))
+        .collect()
+}
+
+/// Same as `solve` with a `HashMap` instead of a vector indexed by the spoken numbers.
+pub fn solve_with_map(numbers: &[u32], nth: usize) -> u32 {
     let mut map = numbers[0..numbers.len() - 1]
         .iter()
         .enumerate()
@@ -21,17 +31,21 @@ fn _solve(input: &str, nth: usize) -> u32 {
     last_spoken
 }
 
-fn solve_test(input: &str, nth: usize) -> u32 {
-    let input = input
-        .split(',')
-        .map(|s| s.parse::<u32>().unwrap())
-        .collect::<Vec<_>>();
-    let mut numbers: Vec<Option<u32>> = vec![None; nth];
-    for index in 1..input.len() {
-        numbers[input[index - 1] as usize] = Some(index as u32);
+/// The spoken numbers are less than `nth`, only the starting ones can be larger.
+pub fn solve(starting: &[u32], nth: usize) -> Result<u32, SolveError> {
+    let largest = starting
+        .iter()
+        .max()
+        .map_or(0, |&number| number as usize + 1);
+    let mut numbers: Vec<Option<u32>> = vec![None; nth.max(largest)];
+    for index in 1..starting.len() {
+        numbers[starting[index - 1] as usize] = Some(index as u32);
     }
-    let mut last_spoken = *input.last().unwrap();
-    for index in input.len()..nth {
+    let mut last_spoken = *starting.last().unwrap();
+    for index in starting.len()..nth {
+        if index % 65536 == 0 {
+            checkpoint()?;
+        }
         let spoken = if let Some(last_index) = numbers[last_spoken as usize] {
             index as u32 - last_index
         } else {
@@ -40,37 +54,69 @@ fn solve_test(input: &str, nth: usize) -> u32 {
         numbers[last_spoken as usize] = Some(index as u32);
         last_spoken = spoken;
     }
-    last_spoken
+    Ok(last_spoken)
 }
 
-pub fn run() {
-    let timer = std::time::Instant::now();
-    let input = std::fs::read_to_string("inputs/day15").unwrap();
-    println!(
-        "day 15 solution 1 : {}, {}us",
-        solve_test(&input, 2020),
-        timer.elapsed().as_micros()
-    );
-    println!(
-        "day 15 solution 2 : {}, {}us",
-        solve_test(&input, 30000000),
-        timer.elapsed().as_micros()
-    );
+pub struct Day15;
+
+impl Solution for Day15 {
+    type Input<'a> = Vec<u32>;
+    type Output1 = Result<u32, SolveError>;
+    type Output2 = Result<u32, SolveError>;
+
+    const DAY: u8 = 15;
+    const TITLE: &'static str = "Rambunctious Recitation";
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(numbers: &Self::Input<'_>) -> Result<u32, SolveError> {
+        solve(numbers, 2020)
+    }
+
+    fn part_2(numbers: &Self::Input<'_>) -> Result<u32, SolveError> {
+        solve(numbers, 30000000)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cancel::CancelFlag;
 
     #[test]
     fn test_solution_1() {
-        let input = "3,1,2";
-        assert_eq!(_solve(&input, 2020), 1836);
+        let numbers = parse_input("3,1,2").unwrap();
+        assert_eq!(solve_with_map(&numbers, 2020), 1836);
     }
 
     #[test]
     fn test_solution_test() {
-        let input = "3,1,2";
-        assert_eq!(solve_test(&input, 2020), 1836);
+        let numbers = parse_input("3,1,2").unwrap();
+        assert_eq!(solve(&numbers, 2020), Ok(1836));
+        // starting numbers larger than the turns
+        let numbers = parse_input("3000,1").unwrap();
+        assert_eq!(solve(&numbers, 2020), Ok(solve_with_map(&numbers, 2020)));
+    }
+
+    #[test]
+    fn test_cancel() {
+        let numbers = parse_input("3,1,2").unwrap();
+        let result = std::thread::spawn(move || {
+            let flag = CancelFlag::new();
+            flag.install();
+            flag.cancel();
+            solve(&numbers, 30000000)
+        });
+        assert_eq!(result.join().unwrap(), Err(SolveError::Cancelled));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert_eq!(
+            parse_input("3,,2"),
+            Err(ParseError::new(1, 3, "", "expected a number"))
+        );
     }
 }
@@ -1,3 +1,8 @@
+use crate::{
+    cancel::checkpoint,
+    error::{next_token, parse_token, ParseError, SolveError},
+    solution::Solution,
+};
 use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone, Copy)]
@@ -17,7 +22,7 @@ impl ValidRange {
     }
 }
 
-struct Input {
+pub struct Input {
     fields: HashMap<String, Vec<ValidRange>>,
     ticket: Vec<u16>,
     nearby_tickets: Vec<Vec<u16>>,
@@ -25,69 +30,49 @@ struct Input {
 }
 
 impl Input {
-    fn new(input: &str) -> Self {
+    pub fn new(input: &str) -> Result<Self, ParseError> {
         let mut parts = input.split("\n\n");
-        let fields = parts
-            .next()
-            .unwrap()
-            .lines()
-            .map(|line| {
-                let mut split = line.split(": ");
-                let field_name = split.next().unwrap().to_owned();
-                let valid_ranges = split
-                    .next()
-                    .unwrap()
-                    .split(" or ")
-                    .map(|range| {
-                        let mut numbers = range.split('-');
-                        let start = numbers.next().unwrap().parse::<u16>().unwrap();
-                        let end = numbers.next().unwrap().parse::<u16>().unwrap();
-                        ValidRange(start, end)
-                    })
-                    .collect::<Vec<_>>();
-                (field_name, valid_ranges)
-            })
-            .collect::<HashMap<_, _>>();
-        let ticket = parts
-            .next()
-            .unwrap()
-            .lines()
-            .skip(1)
-            .next()
-            .unwrap()
-            .split(',')
-            .map(|number| number.parse::<u16>().unwrap())
-            .collect::<Vec<_>>();
-        let nearby_tickets = parts
-            .next()
-            .unwrap()
+        let mut fields = HashMap::new();
+        for line in parts.next().unwrap().lines() {
+            let mut split = line.splitn(2, ": ");
+            let field_name = split.next().unwrap().to_owned();
+            let ranges = next_token(&mut split, line, "expected `: `")
+                .map_err(|error| error.within(input, line))?;
+            let mut valid_ranges = vec![];
+            for range in ranges.split(" or ") {
+                let mut numbers = range.splitn(2, '-');
+                let start = parse_token(input, numbers.next().unwrap(), "expected a number")?;
+                let end = next_token(&mut numbers, range, "expected `-`")
+                    .map_err(|error| error.within(input, range))?;
+                let end = parse_token(input, end, "expected a number")?;
+                valid_ranges.push(ValidRange(start, end));
+            }
+            fields.insert(field_name, valid_ranges);
+        }
+
+        let mut ticket_lines = next_token(&mut parts, input, "expected your ticket")?.lines();
+        next_token(&mut ticket_lines, input, "expected `your ticket:`")?;
+        let ticket = next_token(&mut ticket_lines, input, "expected your ticket")?;
+        let ticket = parse_ticket(input, ticket, fields.len())?;
+        let mut nearby_tickets = vec![];
+        for line in next_token(&mut parts, input, "expected nearby tickets")?
             .lines()
             .skip(1)
-            .map(|line| {
-                line.split(',')
-                    .map(|number| number.parse::<u16>().unwrap())
-                    .collect::<Vec<_>>()
-            })
-            .collect::<Vec<_>>();
+        {
+            nearby_tickets.push(parse_ticket(input, line, fields.len())?);
+        }
 
-        let valid_ranges = merge_ranges(
-            &fields
-                .iter()
-                .map(|(_, ranges)| ranges)
-                .flatten()
-                .copied()
-                .collect::<Vec<_>>(),
-        );
-        Self {
+        let valid_ranges = merge_ranges(&fields.values().flatten().copied().collect::<Vec<_>>());
+        Ok(Self {
             fields,
             ticket,
             nearby_tickets,
             valid_ranges,
-        }
+        })
     }
 
     // better solution could be to merge ranges and to filter values
-    fn solve_1(&self) -> u32 {
+    pub fn solve_1(&self) -> u32 {
         self.nearby_tickets
             .iter()
             .flatten()
@@ -100,7 +85,7 @@ impl Input {
             })
     }
 
-    fn solve_2(&self) -> u64 {
+    pub fn solve_2(&self) -> Result<u64, SolveError> {
         let mut valid_tickets = self
             .nearby_tickets
             .iter()
@@ -112,13 +97,8 @@ impl Input {
             .collect::<Vec<_>>();
         valid_tickets.push(&self.ticket);
 
-        let mut valid_fields_at_index = vec![
-            self.fields
-                .iter()
-                .map(|(name, _)| name)
-                .collect::<HashSet<_>>();
-            self.fields.len()
-        ];
+        let mut valid_fields_at_index =
+            vec![self.fields.keys().collect::<HashSet<_>>(); self.fields.len()];
 
         let departure_field_count = self.fields.iter().fold(0, |acc, (curr, _)| {
             if curr.starts_with("departure") {
@@ -129,6 +109,7 @@ impl Input {
         });
 
         for ticket in valid_tickets.iter() {
+            checkpoint()?;
             for index in 0..valid_fields_at_index.len() {
                 let valid_fields = &mut valid_fields_at_index[index];
                 if valid_fields.len() == 1 {
@@ -154,17 +135,14 @@ impl Input {
                 if valid_fields.len() == 1 {
                     let mut to_remove = vec![];
                     to_remove.push((index, *valid_fields.iter().next().unwrap()));
-                    while to_remove.len() > 0 {
+                    while !to_remove.is_empty() {
                         let mut next_to_remove = vec![];
                         for &(i, field) in &to_remove {
-                            for j in 0..valid_fields_at_index.len() {
-                                if j != i && valid_fields_at_index[j].contains(field) {
-                                    valid_fields_at_index[j].remove(field);
-                                    if valid_fields_at_index[j].len() == 1 {
-                                        next_to_remove.push((
-                                            j,
-                                            *valid_fields_at_index[j].iter().next().unwrap(),
-                                        ));
+                            for (j, fields) in valid_fields_at_index.iter_mut().enumerate() {
+                                if j != i && fields.contains(field) {
+                                    fields.remove(field);
+                                    if fields.len() == 1 {
+                                        next_to_remove.push((j, *fields.iter().next().unwrap()));
                                     }
                                 }
                             }
@@ -183,13 +161,30 @@ impl Input {
                 .map(|(index, _)| index)
                 .collect::<Vec<_>>();
             if departure_fields.len() == departure_field_count {
-                return departure_fields
+                return Ok(departure_fields
                     .iter()
-                    .fold(1, |acc, &index| acc * self.ticket[index] as u64);
+                    .fold(1, |acc, &index| acc * self.ticket[index] as u64));
             }
         }
-        panic!("no solution found");
+        Err(SolveError::NoSolution(
+            "the departure fields cannot be identified".to_owned(),
+        ))
+    }
+}
+
+fn parse_ticket(input: &str, line: &str, length: usize) -> Result<Vec<u16>, ParseError> {
+    let ticket = line
+        .split(',')
+        .map(|number| parse_token(input, number, "expected a number"))
+        .collect::<Result<Vec<_>, _>>()?;
+    if ticket.len() != length {
+        return Err(ParseError::at(
+            input,
+            line,
+            &format!("expected a ticket of {} values", length),
+        ));
     }
+    Ok(ticket)
 }
 
 fn merge_ranges(ranges: &Vec<ValidRange>) -> Vec<ValidRange> {
@@ -218,20 +213,27 @@ fn merge_ranges_once(ranges: &Vec<ValidRange>) -> Vec<ValidRange> {
     vec
 }
 
-pub fn run() {
-    let timer = std::time::Instant::now();
-    let input = std::fs::read_to_string("inputs/day16").unwrap();
-    let input = Input::new(&input);
-    println!(
-        "day 16 solution 1 : {}, {}us",
-        input.solve_1(),
-        timer.elapsed().as_micros()
-    );
-    println!(
-        "day 16 solution 2 : {}, {}us",
-        input.solve_2(),
-        timer.elapsed().as_micros()
-    );
+pub struct Day16;
+
+impl Solution for Day16 {
+    type Input<'a> = Input;
+    type Output1 = u32;
+    type Output2 = Result<u64, SolveError>;
+
+    const DAY: u8 = 16;
+    const TITLE: &'static str = "Ticket Translation";
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        Input::new(input)
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> u32 {
+        input.solve_1()
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Result<u64, SolveError> {
+        input.solve_2()
+    }
 }
 
 #[cfg(test)]
@@ -241,14 +243,23 @@ mod tests {
     #[test]
     fn test_solution_1() {
         let input = "class: 1-3 or 5-7\nrow: 6-11 or 33-44\nseat: 13-40 or 45-50\n\nyour ticket:\n7,1,14\n\nnearby tickets:\n7,3,47\n40,4,50\n55,2,20\n38,6,12";
-        let input = Input::new(&input);
+        let input = Input::new(input).unwrap();
         assert_eq!(input.solve_1(), 71);
     }
 
     #[test]
     fn test_solution_2() {
         let input = "departure class: 0-1 or 4-19\nrow: 0-5 or 8-19\nseat: 0-13 or 16-19\n\nyour ticket:\n11,12,13\n\nnearby tickets:\n3,9,18\n15,1,5\n5,14,9";
-        let input = Input::new(&input);
-        assert_eq!(input.solve_2(), 12);
+        let input = Input::new(input).unwrap();
+        assert_eq!(input.solve_2(), Ok(12));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let input = "class: 1-3 or 5-7\nrow: 6-11 or 33-44\n\nyour ticket:\n7,1\n\nnearby tickets:\n7,3\n40,x";
+        assert_eq!(
+            Input::new(input).err().unwrap(),
+            ParseError::new(9, 4, "x", "expected a number")
+        );
     }
 }
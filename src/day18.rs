@@ -1,167 +1,748 @@
-#[derive(Debug, PartialEq)]
-enum Operator {
+use crate::{
+    bigint::BigInt,
+    error::{parse_token, ParseError, SolveError},
+    solution::Solution,
+};
+use std::{convert::TryFrom, fmt::Display};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Operator {
     Add,
+    Sub,
     Mul,
+    Div,
+    Rem,
+    Pow,
 }
 
 impl Operator {
-    fn parse(input: &str) -> (Self, &str) {
-        match input.chars().next() {
-            Some('*') => (Self::Mul, &input[2..]),
-            Some('+') => (Self::Add, &input[2..]),
-            _ => panic!("invalid operator {}", input),
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            '+' => Some(Self::Add),
+            '-' => Some(Self::Sub),
+            '*' => Some(Self::Mul),
+            '/' => Some(Self::Div),
+            '%' => Some(Self::Rem),
+            '^' => Some(Self::Pow),
+            _ => None,
+        }
+    }
+
+    pub fn symbol(&self) -> char {
+        match self {
+            Self::Add => '+',
+            Self::Sub => '-',
+            Self::Mul => '*',
+            Self::Div => '/',
+            Self::Rem => '%',
+            Self::Pow => '^',
+        }
+    }
+
+    /// Division and remainder truncate toward zero.
+    pub fn apply(&self, lhs: i64, rhs: i64) -> Result<i64, EvalError> {
+        match self {
+            Self::Add => lhs.checked_add(rhs),
+            Self::Sub => lhs.checked_sub(rhs),
+            Self::Mul => lhs.checked_mul(rhs),
+            Self::Div | Self::Rem if rhs == 0 => return Err(EvalError::DivisionByZero),
+            Self::Div => lhs.checked_div(rhs),
+            Self::Rem => lhs.checked_rem(rhs),
+            Self::Pow if rhs < 0 => return Err(EvalError::NegativeExponent),
+            Self::Pow => match lhs {
+                // the only bases which do not overflow with a large exponent
+                0 | 1 => Some(lhs),
+                -1 => Some(if rhs % 2 == 0 { 1 } else { -1 }),
+                _ => u32::try_from(rhs).ok().and_then(|rhs| lhs.checked_pow(rhs)),
+            },
+        }
+        .ok_or(EvalError::Overflow)
+    }
+
+    /// Same as `apply` on big integers, only a huge exponent can overflow : powers are limited to
+    /// `MAX_POW_BITS` bits.
+    pub fn apply_big(&self, lhs: &BigInt, rhs: &BigInt) -> Result<BigInt, EvalError> {
+        match self {
+            Self::Add => Ok(lhs + rhs),
+            Self::Sub => Ok(lhs - rhs),
+            Self::Mul => Ok(lhs * rhs),
+            Self::Div => lhs.checked_div(rhs).ok_or(EvalError::DivisionByZero),
+            Self::Rem => lhs.checked_rem(rhs).ok_or(EvalError::DivisionByZero),
+            Self::Pow if rhs.is_negative() => Err(EvalError::NegativeExponent),
+            Self::Pow => match lhs.to_i128() {
+                Some(0) | Some(1) => Ok(lhs.clone()),
+                Some(-1) if rhs.checked_rem(&BigInt::from(2i64)).unwrap().is_zero() => {
+                    Ok(BigInt::from(1i64))
+                }
+                Some(-1) => Ok(lhs.clone()),
+                _ => rhs
+                    .to_i128()
+                    .and_then(|rhs| u32::try_from(rhs).ok())
+                    .filter(|&rhs| lhs.bits() * rhs as u64 <= MAX_POW_BITS)
+                    .map(|rhs| lhs.pow(rhs))
+                    .ok_or(EvalError::Overflow),
+            },
+        }
+    }
+}
+
+/// Largest power computed on big integers, bounded by the exponent times the bits of the base.
+pub const MAX_POW_BITS: u64 = 1 << 16;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Associativity {
+    Left,
+    Right,
+}
+
+/// Precedence and associativity of the operators of a rule set, higher precedences bind tighter.
+/// Operators missing from the table are rejected by the parser.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OperatorTable {
+    operators: Vec<(Operator, u8, Associativity)>,
+    /// Precedence of the unary minus, which is rejected when not set.
+    unary_minus: Option<u8>,
+}
+
+impl OperatorTable {
+    /// Adds or replaces an operator.
+    pub fn with(
+        mut self,
+        operator: Operator,
+        precedence: u8,
+        associativity: Associativity,
+    ) -> Self {
+        self.operators
+            .retain(|&(current, _, _)| current != operator);
+        self.operators.push((operator, precedence, associativity));
+        self
+    }
+
+    pub fn with_unary_minus(mut self, precedence: u8) -> Self {
+        self.unary_minus = Some(precedence);
+        self
+    }
+
+    pub fn get(&self, operator: Operator) -> Option<(u8, Associativity)> {
+        self.operators
+            .iter()
+            .find(|&&(current, _, _)| current == operator)
+            .map(|&(_, precedence, associativity)| (precedence, associativity))
+    }
+
+    /// Rules of the first part : addition and multiplication evaluated left to right.
+    pub fn left_to_right() -> Self {
+        Self::default()
+            .with(Operator::Add, 1, Associativity::Left)
+            .with(Operator::Mul, 1, Associativity::Left)
+    }
+
+    /// Rules of the second part : addition evaluated before multiplication.
+    pub fn addition_first() -> Self {
+        Self::default()
+            .with(Operator::Add, 2, Associativity::Left)
+            .with(Operator::Mul, 1, Associativity::Left)
+    }
+
+    /// Usual rules, exponentiation is right associative and binds tighter than the unary minus.
+    pub fn standard() -> Self {
+        Self::default()
+            .with(Operator::Add, 1, Associativity::Left)
+            .with(Operator::Sub, 1, Associativity::Left)
+            .with(Operator::Mul, 2, Associativity::Left)
+            .with(Operator::Div, 2, Associativity::Left)
+            .with(Operator::Rem, 2, Associativity::Left)
+            .with_unary_minus(3)
+            .with(Operator::Pow, 4, Associativity::Right)
+    }
+
+    fn symbols(&self) -> Vec<String> {
+        self.operators
+            .iter()
+            .map(|(operator, _, _)| operator.symbol().to_string())
+            .collect()
+    }
+}
+
+/// `a, b or c` like list of the expected tokens.
+fn one_of(items: &[String]) -> String {
+    match items.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, others)) => format!("{} or {}", others.join(", "), last),
+        None => "nothing".to_owned(),
+    }
+}
+
+/// Finds the parentheses without a match before parsing, so the error is reported on the
+/// parenthesis rather than where the parser notices something is missing.
+fn check_parentheses(input: &str, tokens: &[Token<'_>]) -> Result<(), ParseError> {
+    let mut open = vec![];
+    for token in tokens {
+        match token.kind {
+            TokenKind::LeftParenthesis => open.push(token),
+            TokenKind::RightParenthesis => {
+                open.pop().ok_or_else(|| {
+                    ParseError::at(input, token.text, "unmatched `)`, no `(` before it")
+                })?;
+            }
+            _ => {}
         }
     }
+    match open.pop() {
+        Some(token) => Err(ParseError::at(input, token.text, "unclosed parenthesis")),
+        None => Ok(()),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TokenKind {
+    Number(i64),
+    Operator(Operator),
+    LeftParenthesis,
+    RightParenthesis,
+}
+
+/// Token along with its text, a slice of the input to locate errors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Token<'a> {
+    kind: TokenKind,
+    text: &'a str,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token<'_>>, ParseError> {
+    let mut tokens = vec![];
+    let mut chars = input.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let mut end = start + c.len_utf8();
+        let kind = match c {
+            c if c.is_whitespace() => continue,
+            '(' => TokenKind::LeftParenthesis,
+            ')' => TokenKind::RightParenthesis,
+            '0'..='9' => {
+                while let Some(&(index, '0'..='9')) = chars.peek() {
+                    end = index + 1;
+                    chars.next();
+                }
+                TokenKind::Number(parse_token(input, &input[start..end], "number too large")?)
+            }
+            c => match Operator::from_char(c) {
+                Some(operator) => TokenKind::Operator(operator),
+                None => {
+                    return Err(ParseError::at(
+                        input,
+                        &input[start..end],
+                        "invalid character, expected a number, an operator or a parenthesis",
+                    ))
+                }
+            },
+        };
+        tokens.push(Token {
+            kind,
+            text: &input[start..end],
+        });
+    }
+    Ok(tokens)
 }
 
-#[derive(Debug)]
-enum Operand {
-    Number(u64),
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expression {
+    Number(i64),
+    Negate(Box<Expression>),
     Operation(Box<Operation>),
 }
 
-impl Operand {
-    fn parse(input: &str, use_precedence: bool) -> (Self, Option<&str>) {
-        let mut chars = input.char_indices();
-        match chars.next() {
-            Some((_, '(')) => {
-                // find matching parenthesis and parse operation from str
-                let mut deepness = 0;
-                for (index, c) in chars {
-                    match c {
-                        '(' => {
-                            deepness += 1;
-                        }
-                        ')' if deepness == 0 => {
-                            let rest = if index == input.len() - 1 {
-                                None
-                            } else {
-                                Some(&input[index + 2..])
-                            };
-                            return (
-                                Self::Operation(Box::new(Operation::parse(&input[1..index], use_precedence))),
-                                rest,
-                            );
-                        }
-                        ')' if deepness > 0 => {
-                            deepness -= 1;
-                        }
-                        _ => {}
+impl Expression {
+    /// Parses a whole line with the given rules.
+    pub fn parse(input: &str, table: &OperatorTable) -> Result<Self, ParseError> {
+        let tokens = tokenize(input)?;
+        check_parentheses(input, &tokens)?;
+        let mut parser = Parser {
+            input,
+            tokens,
+            position: 0,
+            depth: 0,
+            calls: 0,
+            table,
+        };
+        let (expression, _) = parser.expression(0)?;
+        // the parentheses are balanced so the expression can only stop early on an error
+        debug_assert_eq!(parser.position, parser.tokens.len());
+        Ok(expression)
+    }
+
+    pub fn evaluate(&self) -> Result<i64, EvalError> {
+        match self {
+            &Self::Number(value) => Ok(value),
+            Self::Negate(expression) => expression
+                .evaluate()?
+                .checked_neg()
+                .ok_or(EvalError::Overflow),
+            Self::Operation(operation) => operation.solve(),
+        }
+    }
+
+    /// Evaluates with arbitrary precision, when `evaluate` overflows.
+    pub fn evaluate_big(&self) -> Result<BigInt, EvalError> {
+        match self {
+            &Self::Number(value) => Ok(BigInt::from(value)),
+            Self::Negate(expression) => Ok(-&expression.evaluate_big()?),
+            Self::Operation(operation) => operation.solve_big(),
+        }
+    }
+}
+
+/// Deepest expression tree and parser recursion accepted, parsing and evaluating recurse once
+/// per level.
+pub const MAX_DEPTH: usize = 500;
+
+/// Pratt parser : operands are parsed by `prefix`, then operators are consumed while they bind
+/// at least as tight as `min_precedence`.
+struct Parser<'a, 't> {
+    input: &'a str,
+    tokens: Vec<Token<'a>>,
+    position: usize,
+    /// Number of parentheses opened around the current token.
+    depth: usize,
+    /// Number of calls to `expression` in progress.
+    calls: usize,
+    table: &'t OperatorTable,
+}
+
+impl<'a> Parser<'a, '_> {
+    fn next(&mut self) -> Option<Token<'a>> {
+        let token = self.tokens.get(self.position).copied();
+        self.position += 1;
+        token
+    }
+
+    fn too_deep(&self, token: &str) -> ParseError {
+        ParseError::at(
+            self.input,
+            token,
+            &format!("expression nested more than {} levels deep", MAX_DEPTH),
+        )
+    }
+
+    /// The expression and the depth of its tree.
+    fn expression(&mut self, min_precedence: u16) -> Result<(Expression, usize), ParseError> {
+        if self.calls == MAX_DEPTH {
+            let token = self
+                .tokens
+                .get(self.position)
+                .map_or("", |token| token.text);
+            return Err(self.too_deep(token));
+        }
+        self.calls += 1;
+        let expression = self.operations(min_precedence);
+        self.calls -= 1;
+        expression
+    }
+
+    /// `min_precedence` is wider than the precedences, so it is above any of them after a left
+    /// associative operator of the highest precedence.
+    fn operations(&mut self, min_precedence: u16) -> Result<(Expression, usize), ParseError> {
+        let (mut lhs, mut lhs_depth) = self.prefix()?;
+        while let Some(token) = self.tokens.get(self.position).copied() {
+            let operator = match token.kind {
+                TokenKind::Operator(operator) => operator,
+                TokenKind::RightParenthesis => break,
+                _ => {
+                    let mut expected = self.table.symbols();
+                    if self.depth > 0 {
+                        expected.push("`)`".to_owned());
                     }
+                    return Err(ParseError::at(
+                        self.input,
+                        token.text,
+                        &format!("expected {}", one_of(&expected)),
+                    ));
                 }
-                panic!("invalid operation");
+            };
+            let (precedence, associativity) = self.table.get(operator).ok_or_else(|| {
+                ParseError::at(
+                    self.input,
+                    token.text,
+                    &format!(
+                        "invalid operator, expected {}",
+                        one_of(&self.table.symbols())
+                    ),
+                )
+            })?;
+            let precedence = u16::from(precedence);
+            if precedence < min_precedence {
+                break;
+            }
+            self.position += 1;
+            let (rhs, rhs_depth) = self.expression(match associativity {
+                Associativity::Left => precedence + 1,
+                Associativity::Right => precedence,
+            })?;
+            lhs_depth = lhs_depth.max(rhs_depth) + 1;
+            if lhs_depth > MAX_DEPTH {
+                return Err(self.too_deep(token.text));
             }
-            _ => {
-                // extract number
-                let mut split = input.splitn(2, ' ');
-                let operand = split.next().unwrap().parse::<u64>().unwrap();
-                let rest = split.next();
-                (Self::Number(operand), rest)
+            lhs = Expression::Operation(Box::new(Operation { operator, lhs, rhs }));
+        }
+        Ok((lhs, lhs_depth))
+    }
+
+    fn prefix(&mut self) -> Result<(Expression, usize), ParseError> {
+        let expected = if self.table.unary_minus.is_some() {
+            "expected a number, `(` or `-`"
+        } else {
+            "expected a number or `(`"
+        };
+        let token = self
+            .next()
+            .ok_or_else(|| ParseError::at(self.input, &self.input[self.input.len()..], expected))?;
+        match token.kind {
+            TokenKind::Number(value) => Ok((Expression::Number(value), 1)),
+            TokenKind::LeftParenthesis => {
+                self.depth += 1;
+                let expression = self.expression(0)?;
+                self.depth -= 1;
+                // the matching parenthesis, the only token stopping an expression
+                self.next();
+                Ok(expression)
+            }
+            TokenKind::Operator(Operator::Sub) if self.table.unary_minus.is_some() => {
+                let (operand, depth) =
+                    self.expression(u16::from(self.table.unary_minus.unwrap()))?;
+                if depth == MAX_DEPTH {
+                    return Err(self.too_deep(token.text));
+                }
+                Ok((Expression::Negate(Box::new(operand)), depth + 1))
             }
+            _ => Err(ParseError::at(self.input, token.text, expected)),
         }
     }
 }
 
-#[derive(Debug)]
-struct Operation {
+#[derive(Clone, Debug, PartialEq)]
+pub struct Operation {
     operator: Operator,
-    lhs: Operand,
-    rhs: Operand,
+    lhs: Expression,
+    rhs: Expression,
 }
 
 impl Operation {
-    fn parse(input: &str, use_precedence: bool) -> Self {
-        let (lhs, r) = Operand::parse(input, use_precedence);
-        let (operator, r) = Operator::parse(r.unwrap());
-        let (rhs, r) = Operand::parse(r, use_precedence);
-
-        let mut operation = Self { lhs, operator, rhs };
-        let mut rest = r;
-        while let Some(r) = rest {
-            if r.len() == 0 {
-                break;
-            }
-            let (operator, r) = Operator::parse(r);
-            let (rhs, r) = Operand::parse(r, use_precedence);
-
-            if use_precedence && operator == Operator::Add && operation.operator == Operator::Mul {
-                operation.rhs = Operand::Operation(Box::new(Self {
-                    lhs: operation.rhs,
-                    operator,
-                    rhs,
-                }));
-            } else {
-                operation = Self {
-                    lhs: Operand::Operation(Box::new(operation)),
-                    operator,
-                    rhs,
-                };
-            }
+    /// Parses a line made of at least one operation, with the rules of the first part or of the
+    /// second part when `use_precedence` is set.
+    pub fn parse(input: &str, use_precedence: bool) -> Result<Self, ParseError> {
+        let table = if use_precedence {
+            OperatorTable::addition_first()
+        } else {
+            OperatorTable::left_to_right()
+        };
+        match Expression::parse(input, &table)? {
+            Expression::Operation(operation) => Ok(*operation),
+            _ => Err(ParseError::at(
+                input,
+                &input[input.trim_end().len()..],
+                "expected an operator",
+            )),
+        }
+    }
+
+    pub fn solve(&self) -> Result<i64, EvalError> {
+        self.operator
+            .apply(self.lhs.evaluate()?, self.rhs.evaluate()?)
+    }
 
-            rest = r;
+    pub fn solve_big(&self) -> Result<BigInt, EvalError> {
+        self.operator
+            .apply_big(&self.lhs.evaluate_big()?, &self.rhs.evaluate_big()?)
+    }
+}
+
+/// Fully parenthesised, to show how the operators were grouped.
+impl Display for Expression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Number(value) => write!(f, "{}", value),
+            Self::Negate(expression) => write!(f, "(-{})", expression),
+            Self::Operation(operation) => write!(f, "{}", operation),
         }
-        operation
     }
+}
 
-    fn solve(&self) -> u64 {
-        let lhs = match &self.lhs {
-            &Operand::Number(n) => n,
-            Operand::Operation(o) => o.solve(),
-        };
-        let rhs = match &self.rhs {
-            &Operand::Number(n) => n,
-            Operand::Operation(o) => o.solve(),
-        };
-        match &self.operator {
-            Operator::Mul => lhs * rhs,
-            Operator::Add => lhs + rhs,
+impl Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({} {} {})", self.lhs, self.operator.symbol(), self.rhs)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EvalError {
+    DivisionByZero,
+    NegativeExponent,
+    Overflow,
+}
+
+impl Display for EvalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvalError::DivisionByZero => write!(f, "division by zero"),
+            EvalError::NegativeExponent => write!(f, "negative exponent"),
+            EvalError::Overflow => write!(f, "overflow"),
         }
     }
 }
 
-fn solve(input: &str, use_precedence: bool) -> u64 {
+pub fn parse_input(input: &str, table: &OperatorTable) -> Result<Vec<Expression>, ParseError> {
     input
         .lines()
-        .fold(0u64, |acc, curr| acc + Operation::parse(curr, use_precedence).solve())
+        .map(|line| Expression::parse(line, table).map_err(|error| error.within(input, line)))
+        .collect()
+}
+
+/// Sum of the expressions, computed with `i64` and again with big integers if anything overflows.
+pub fn solve(expressions: &[Expression]) -> Result<BigInt, EvalError> {
+    let sum = expressions.iter().try_fold(0i64, |acc, expression| {
+        Operator::Add.apply(acc, expression.evaluate()?)
+    });
+    match sum {
+        Ok(sum) => Ok(BigInt::from(sum)),
+        Err(EvalError::Overflow) => expressions
+            .iter()
+            .try_fold(BigInt::zero(), |acc, expression| {
+                Ok(&acc + &expression.evaluate_big()?)
+            }),
+        Err(error) => Err(error),
+    }
 }
 
-pub fn run() {
-    let timer = std::time::Instant::now();
-    let input = std::fs::read_to_string("inputs/day18").unwrap();
-    println!(
-        "day 18 solution 1 : {}, {}us",
-        solve(&input, false),
-        timer.elapsed().as_micros()
-    );
-    println!(
-        "day 18 solution 2 : {}, {}us",
-        solve(&input, true),
-        timer.elapsed().as_micros()
-    );
+fn solve_part(expressions: &[Expression]) -> Result<BigInt, SolveError> {
+    solve(expressions).map_err(|error| SolveError::NoSolution(error.to_string()))
+}
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    /// Expressions parsed without then with precedence of addition over multiplication.
+    type Input<'a> = (Vec<Expression>, Vec<Expression>);
+    type Output1 = Result<BigInt, SolveError>;
+    type Output2 = Result<BigInt, SolveError>;
+
+    const DAY: u8 = 18;
+    const TITLE: &'static str = "Operation Order";
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        Ok((
+            parse_input(input, &OperatorTable::left_to_right())?,
+            parse_input(input, &OperatorTable::addition_first())?,
+        ))
+    }
+
+    fn part_1((expressions, _): &Self::Input<'_>) -> Result<BigInt, SolveError> {
+        solve_part(expressions)
+    }
+
+    fn part_2((_, expressions): &Self::Input<'_>) -> Result<BigInt, SolveError> {
+        solve_part(expressions)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::solution::Answer;
+
+    fn evaluate(input: &str, table: &OperatorTable) -> Result<i64, EvalError> {
+        Expression::parse(input, table).unwrap().evaluate()
+    }
 
     #[test]
     fn test() {
         let input = "2 * 3 + (4 * 5)";
-        let operation = Operation::parse(input, false);
-        assert_eq!(operation.solve(), 26);
+        let operation = Operation::parse(input, false).unwrap();
+        assert_eq!(operation.solve(), Ok(26));
     }
 
     #[test]
     fn test_2() {
         let input = "((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2";
-        let operation = Operation::parse(input, false);
-        assert_eq!(operation.solve(), 13632);
+        let operation = Operation::parse(input, false).unwrap();
+        assert_eq!(operation.solve(), Ok(13632));
     }
 
     #[test]
     fn test_3() {
         let input = "2 * 3 + (4 * 5)";
-        let operation = Operation::parse(input, true);
-        assert_eq!(operation.solve(), 46);
+        let operation = Operation::parse(input, true).unwrap();
+        assert_eq!(operation.solve(), Ok(46));
+    }
+
+    #[test]
+    fn test_standard() {
+        let table = OperatorTable::standard();
+        assert_eq!(evaluate("2+3*4", &table), Ok(14));
+        assert_eq!(evaluate("  (2 +3)*\t4 ", &table), Ok(20));
+        assert_eq!(evaluate("10 - 4 - 3", &table), Ok(3));
+        assert_eq!(evaluate("2 ^ 3 ^ 2", &table), Ok(512));
+        assert_eq!(evaluate("-2 ^ 2", &table), Ok(-4));
+        assert_eq!(evaluate("--3 - -4", &table), Ok(7));
+        assert_eq!(evaluate("17 % 5 * 3 / 2", &table), Ok(3));
+        assert_eq!(
+            evaluate("1 / (2 - 2)", &table),
+            Err(EvalError::DivisionByZero)
+        );
+        assert_eq!(evaluate("2 ^ -1", &table), Err(EvalError::NegativeExponent));
+        assert_eq!(evaluate("2 ^ 63", &table), Err(EvalError::Overflow));
+        assert_eq!(evaluate("(-1) ^ 9999999999", &table), Ok(-1));
+    }
+
+    #[test]
+    fn test_custom_table() {
+        // subtraction first, right to left
+        let table = OperatorTable::left_to_right()
+            .with(Operator::Sub, 2, Associativity::Right)
+            .with(Operator::Add, 0, Associativity::Left);
+        assert_eq!(evaluate("2 * 10 - 4 - 3 + 1", &table), Ok(19));
+        // the highest precedence is still left associative
+        let table = table.with(Operator::Sub, 255, Associativity::Left);
+        assert_eq!(evaluate("10 - 4 - 3 + 1", &table), Ok(4));
+    }
+
+    #[test]
+    fn test_big() {
+        let table = OperatorTable::standard();
+        let big = |input: &str| {
+            Expression::parse(input, &table)
+                .unwrap()
+                .evaluate_big()
+                .map(|value| value.to_string())
+        };
+        assert_eq!(
+            evaluate("9999999999 * 9999999999", &table),
+            Err(EvalError::Overflow)
+        );
+        assert_eq!(
+            big("9999999999 * 9999999999 * 9999999999").as_deref(),
+            Ok("999999999700000000029999999999")
+        );
+        assert_eq!(big("-(2 ^ 100) / 3 % 1000").as_deref(), Ok("-125"));
+        assert_eq!(big("(-1) ^ 9999999999").as_deref(), Ok("-1"));
+        assert_eq!(big("3 ^ 9999999999"), Err(EvalError::Overflow));
+        assert_eq!(big("3 ^ 4000000000"), Err(EvalError::Overflow));
+        assert_eq!(big("2 ^ 5000").map(|value| value.len()), Ok(1506));
+        assert_eq!(big("(2 ^ 100) ^ 700"), Err(EvalError::Overflow));
+        assert_eq!(big("(2 ^ 70) % 0"), Err(EvalError::DivisionByZero));
+
+        // the sum overflows while every line fits
+        let expressions = parse_input(
+            "9223372036854775807\n9223372036854775807 * 1\n2",
+            &OperatorTable::left_to_right(),
+        )
+        .unwrap();
+        assert_eq!(
+            solve(&expressions).unwrap().to_string(),
+            "18446744073709551616"
+        );
+        assert_eq!(
+            Answer::from(solve(&expressions).unwrap()),
+            Answer::Integer(1 << 64)
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        fn parse(input: &str, table: &OperatorTable) -> String {
+            Expression::parse(input, table).unwrap().to_string()
+        }
+        assert_eq!(
+            parse("2 * 3 + (4 * 5)", &OperatorTable::left_to_right()),
+            "((2 * 3) + (4 * 5))"
+        );
+        assert_eq!(
+            parse("2 * 3 + (4 * 5)", &OperatorTable::addition_first()),
+            "(2 * (3 + (4 * 5)))"
+        );
+        assert_eq!(
+            parse("-2 ^ 3 ^ 2 - 1", &OperatorTable::standard()),
+            "((-(2 ^ (3 ^ 2))) - 1)"
+        );
+        assert_eq!(parse("((7))", &OperatorTable::standard()), "7");
+    }
+
+    #[test]
+    fn test_parentheses() {
+        let table = OperatorTable::left_to_right();
+        assert_eq!(
+            Expression::parse("(1 + 2)) * 3", &table),
+            Err(ParseError::new(
+                1,
+                8,
+                ")",
+                "unmatched `)`, no `(` before it"
+            ))
+        );
+        assert_eq!(
+            Expression::parse("((1 + 2) * 3", &table),
+            Err(ParseError::new(1, 1, "(", "unclosed parenthesis"))
+        );
+        assert_eq!(
+            Expression::parse("(1 + 2 3)", &table),
+            Err(ParseError::new(1, 8, "3", "expected +, * or `)`"))
+        );
+        assert_eq!(
+            Expression::parse("(1 + )", &table),
+            Err(ParseError::new(1, 6, ")", "expected a number or `(`"))
+        );
+        assert_eq!(
+            Expression::parse("1 +", &table),
+            Err(ParseError::new(1, 4, "", "expected a number or `(`"))
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let input = "1 + 2\n2 * (3 + 4";
+        assert_eq!(
+            parse_input(input, &OperatorTable::left_to_right())
+                .err()
+                .unwrap(),
+            ParseError::new(2, 5, "(", "unclosed parenthesis")
+        );
+        let input = "2 * (3 - 4)";
+        assert_eq!(
+            parse_input(input, &OperatorTable::addition_first())
+                .err()
+                .unwrap(),
+            ParseError::new(1, 8, "-", "invalid operator, expected + or *")
+        );
+        assert_eq!(
+            Expression::parse("-1", &OperatorTable::left_to_right()),
+            Err(ParseError::new(1, 1, "-", "expected a number or `(`"))
+        );
+        assert_eq!(
+            Expression::parse("1 + 2 3", &OperatorTable::standard()),
+            Err(ParseError::new(1, 7, "3", "expected +, -, *, /, % or ^"))
+        );
+        assert_eq!(
+            Operation::parse("12", false),
+            Err(ParseError::new(1, 3, "", "expected an operator"))
+        );
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let table = OperatorTable::standard();
+        let too_deep = |input: &str| {
+            Expression::parse(input, &table)
+                .err()
+                .is_some_and(|error| error.message.contains("levels deep"))
+        };
+        // a flat line is a tree as deep as its operators
+        let flat = |terms: usize| vec!["1"; terms].join(" + ");
+        assert_eq!(evaluate(&flat(MAX_DEPTH), &table), Ok(MAX_DEPTH as i64));
+        assert!(too_deep(&flat(MAX_DEPTH + 1)));
+        assert!(too_deep(&flat(300000)));
+        assert!(too_deep(&vec!["2"; 300000].join(" ^ ")));
+
+        let nested = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(evaluate(&nested(MAX_DEPTH - 1), &table), Ok(1));
+        assert!(too_deep(&nested(200000)));
+        assert!(too_deep(&"-".repeat(200000)));
     }
 }
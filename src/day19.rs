@@ -1,89 +1,633 @@
-use std::collections::HashMap;
+use crate::{
+    error::{next_token, parse_token, ParseError, SolveError},
+    solution::Solution,
+};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt::Display,
+};
 
-struct RuleSet<'a> {
-    rules: HashMap<&'a str, &'a str>,
+/// Rules replacing 8 and 11 in the second part, they are recursive so the language is no longer
+/// regular.
+pub const LOOPING_RULES: &str = "8: 42 | 42 8\n11: 42 31 | 42 11 31";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Rule {
+    Char(char),
+    /// Alternatives, each one a sequence of rule numbers.
+    Sequences(Vec<Vec<usize>>),
 }
 
-impl<'a> RuleSet<'a> {
-    fn new(input: &'a str, to_update: HashMap<&'a str, &'a str>) -> Self {
-        let rules = input
-            .lines()
-            .map(|rule| {
-                let mut rule_parts = rule.split(": ");
-                let rule_number = rule_parts.next().unwrap();
-                let rule_definition = if to_update.contains_key(rule_number) {
-                    to_update.get(rule_number).unwrap()
-                } else {
-                    rule_parts.next().unwrap()
-                };
-                (rule_number, rule_definition)
-            })
-            .collect::<HashMap<_, _>>();
-        Self { rules }
-    }
-
-    fn match_rule_start(&self, message: &'a str, rule_number: &str) -> Vec<&str> {
-        let rule = self.rules.get(rule_number);
-        match rule {
-            Some(char_rule) if char_rule.contains('"') => {
-                if message.starts_with(&char_rule[1..2]) {
-                    vec![&message[1..]]
-                } else {
-                    vec![]
+/// The rules of a grammar, parsed once and compiled into a `Matcher` to check the messages.
+#[derive(Clone, Debug, Default)]
+pub struct RuleSet {
+    rules: HashMap<usize, Rule>,
+}
+
+impl RuleSet {
+    pub fn new(input: &str) -> Result<Self, ParseError> {
+        let mut rule_set = Self::default();
+        rule_set.update(input)?;
+        Ok(rule_set)
+    }
+
+    /// Adds the rules of `input`, replacing the ones with the same numbers, every rule used must
+    /// be defined in the set or in `input`. The set is left unchanged on errors.
+    pub fn update(&mut self, input: &str) -> Result<(), ParseError> {
+        let mut rules = HashMap::new();
+        let mut references = vec![];
+        for line in input.lines() {
+            let (number, rule, sub_rules) = parse_rule(input, line)?;
+            rules.insert(number, rule);
+            references.extend(sub_rules);
+        }
+        if let Some((_, token)) = references
+            .into_iter()
+            .find(|(number, _)| !rules.contains_key(number) && !self.rules.contains_key(number))
+        {
+            return Err(ParseError::at(input, token, "undefined rule"));
+        }
+        self.rules.extend(rules);
+        Ok(())
+    }
+
+    pub fn rule(&self, number: usize) -> Option<&Rule> {
+        self.rules.get(&number)
+    }
+
+    /// A rule is regular when no rule it uses, itself included, is recursive.
+    pub fn is_regular(&self, number: usize) -> bool {
+        self.check_regular(number, &mut vec![], &mut HashMap::new())
+    }
+
+    fn check_regular(
+        &self,
+        number: usize,
+        stack: &mut Vec<usize>,
+        regular: &mut HashMap<usize, bool>,
+    ) -> bool {
+        if stack.contains(&number) {
+            return false;
+        }
+        if let Some(&result) = regular.get(&number) {
+            return result;
+        }
+        stack.push(number);
+        let mut result = true;
+        for &sub_rule in self.sub_rules(number) {
+            // no early exit, so that every rule on a cycle is found
+            result &= self.check_regular(sub_rule, stack, regular);
+        }
+        stack.pop();
+        regular.insert(number, result);
+        result
+    }
+
+    fn sub_rules(&self, number: usize) -> impl Iterator<Item = &usize> {
+        let sequences = match self.rules.get(&number) {
+            Some(Rule::Sequences(sequences)) => &sequences[..],
+            _ => &[],
+        };
+        sequences.iter().flatten()
+    }
+
+    /// Compiles the messages matching `start`.
+    ///
+    /// A regular rule becomes a single DFA, unless its automata would have too many states. The
+    /// rules without an automaton are matched by an Earley recogniser, which uses the automata of
+    /// the largest regular rules below them.
+    pub fn compile(&self, start: usize) -> Matcher<'_> {
+        let mut regular = HashMap::new();
+        self.check_regular(start, &mut vec![], &mut regular);
+        let mut automata = HashMap::new();
+        let mut visited = HashSet::new();
+        let mut pending = vec![start];
+        while let Some(number) = pending.pop() {
+            if !visited.insert(number) {
+                continue;
+            }
+            if regular.get(&number) == Some(&true) {
+                if let Some(dfa) = Nfa::new(self, number).and_then(|nfa| Dfa::new(&nfa)) {
+                    automata.insert(number, dfa);
+                    continue;
                 }
             }
-            Some(sub_rules) => sub_rules
-                .split(" | ")
-                .flat_map(|parts| {
-                    parts.split(" ").fold(vec![message], |rests, sub_rule| {
-                        rests
-                            .iter()
-                            .flat_map(|rest| self.match_rule_start(rest, sub_rule))
-                            .collect()
-                    })
-                })
-                .collect(),
-            _ => panic!("should not happen"),
+            pending.extend(self.sub_rules(number));
+        }
+        Matcher {
+            rule_set: self,
+            start,
+            automata,
         }
     }
+
+    /// How `message` matches rule `start`, if it does.
+    pub fn derive(&self, start: usize, message: &str) -> Option<Derivation> {
+        let message = message.chars().collect::<Vec<_>>();
+        let automata = HashMap::new();
+        let mut earley = Earley::new(self, &automata, &message);
+        if !earley.matches(start) {
+            return None;
+        }
+        earley.derive(start, 0, message.len(), &mut vec![])
+    }
 }
 
-fn solve(rule_set: &RuleSet, messages: &str) -> u32 {
-    messages.lines().fold(0, |acc, message| {
-        let result = rule_set.match_rule_start(message, "0");
-        if result.contains(&"") {
-            acc + 1
-        } else {
-            acc
+/// A rule used in a definition, with its token.
+type Reference<'a> = (usize, &'a str);
+
+/// Parses a line, along with the rules it uses.
+fn parse_rule<'a>(
+    input: &str,
+    line: &'a str,
+) -> Result<(usize, Rule, Vec<Reference<'a>>), ParseError> {
+    let mut parts = line.splitn(2, ": ");
+    let number = parse_token(input, parts.next().unwrap(), "expected a rule number")?;
+    let definition =
+        next_token(&mut parts, line, "expected `: `").map_err(|error| error.within(input, line))?;
+    if definition.starts_with('"') {
+        let mut chars = definition.chars();
+        return match (chars.next(), chars.next(), chars.next(), chars.next()) {
+            (_, Some(c), Some('"'), None) if c != '"' => Ok((number, Rule::Char(c), vec![])),
+            _ => Err(ParseError::at(
+                input,
+                definition,
+                "expected a single character between quotes",
+            )),
+        };
+    }
+    let mut sub_rules = vec![];
+    let sequences = definition
+        .split(" | ")
+        .map(|sequence| {
+            sequence
+                .split(' ')
+                .map(|token| {
+                    let sub_rule = parse_token(input, token, "expected a rule number")?;
+                    sub_rules.push((sub_rule, token));
+                    Ok(sub_rule)
+                })
+                .collect()
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((number, Rule::Sequences(sequences), sub_rules))
+}
+
+/// Largest automata built by `RuleSet::compile`, inlining the rules or the subset construction
+/// can grow exponentially with the size of the grammar.
+const MAX_NFA_STATES: usize = 1 << 12;
+/// Largest deterministic automaton, see `MAX_NFA_STATES`.
+const MAX_DFA_STATES: usize = 1 << 10;
+
+/// Nondeterministic automaton of a regular rule, built by inlining every rule it uses.
+struct Nfa {
+    transitions: Vec<Vec<(char, usize)>>,
+    epsilon: Vec<Vec<usize>>,
+    accepting: usize,
+}
+
+impl Nfa {
+    /// `None` when the automaton has more than `MAX_NFA_STATES` states.
+    fn new(rule_set: &RuleSet, number: usize) -> Option<Self> {
+        let mut nfa = Self {
+            transitions: vec![vec![]],
+            epsilon: vec![vec![]],
+            accepting: 0,
+        };
+        nfa.accepting = nfa.build(rule_set, number, 0)?;
+        Some(nfa)
+    }
+
+    fn add_state(&mut self) -> Option<usize> {
+        if self.transitions.len() == MAX_NFA_STATES {
+            return None;
+        }
+        self.transitions.push(vec![]);
+        self.epsilon.push(vec![]);
+        Some(self.transitions.len() - 1)
+    }
+
+    /// Adds the states matching rule `number` from state `from`, returns the state reached after
+    /// the match.
+    fn build(&mut self, rule_set: &RuleSet, number: usize, from: usize) -> Option<usize> {
+        let to = self.add_state()?;
+        match rule_set.rule(number) {
+            Some(&Rule::Char(c)) => self.transitions[from].push((c, to)),
+            Some(Rule::Sequences(sequences)) => {
+                for sequence in sequences {
+                    let mut end = from;
+                    for &sub_rule in sequence {
+                        end = self.build(rule_set, sub_rule, end)?;
+                    }
+                    self.epsilon[end].push(to);
+                }
+            }
+            // nothing leads to `to`
+            None => {}
+        }
+        Some(to)
+    }
+
+    fn closure(&self, states: impl IntoIterator<Item = usize>) -> BTreeSet<usize> {
+        let mut closure = BTreeSet::new();
+        let mut stack = states.into_iter().collect::<Vec<_>>();
+        while let Some(state) = stack.pop() {
+            if closure.insert(state) {
+                stack.extend(&self.epsilon[state]);
+            }
         }
-    })
+        closure
+    }
 }
 
-pub fn run() {
-    let timer = std::time::Instant::now();
-    let input = std::fs::read_to_string("inputs/day19").unwrap();
-    let mut input_parts = input.split("\n\n");
-    let rules = input_parts.next().unwrap();
-    let messages = input_parts.next().unwrap();
+/// Deterministic automaton, a missing transition rejects the message.
+#[derive(Clone, Debug)]
+pub struct Dfa {
+    alphabet: Vec<char>,
+    /// Next state for each state and letter of the alphabet.
+    transitions: Vec<Vec<Option<usize>>>,
+    accepting: Vec<bool>,
+}
 
-    let rule_set = RuleSet::new(rules, HashMap::new());
-    println!(
-        "day 19 solution 1 : {}, {}us",
-        solve(&rule_set, messages),
-        timer.elapsed().as_micros()
-    );
-    let rule_set = RuleSet::new(
-        rules,
-        [("8", "42 | 42 8"), ("11", "42 31 | 42 11 31")]
+impl Dfa {
+    /// Subset construction, the state 0 being the start. `None` when the automaton has more
+    /// than `MAX_DFA_STATES` states.
+    fn new(nfa: &Nfa) -> Option<Self> {
+        let mut alphabet = nfa
+            .transitions
             .iter()
-            .copied()
-            .collect(),
-    );
-    println!(
-        "day 19 solution 2 : {}, {}us",
-        solve(&rule_set, messages),
-        timer.elapsed().as_micros()
-    );
+            .flatten()
+            .map(|&(c, _)| c)
+            .collect::<Vec<_>>();
+        alphabet.sort_unstable();
+        alphabet.dedup();
+
+        let start = nfa.closure([0]);
+        let mut dfa = Self {
+            alphabet,
+            transitions: vec![],
+            accepting: vec![],
+        };
+        let mut states = HashMap::new();
+        let mut subsets = vec![];
+        states.insert(start.clone(), 0);
+        subsets.push(start);
+        while let Some(subset) = subsets.get(dfa.transitions.len()).cloned() {
+            let mut transitions = vec![];
+            for &c in &dfa.alphabet {
+                let targets = nfa.closure(subset.iter().flat_map(|&state| {
+                    nfa.transitions[state]
+                        .iter()
+                        .filter(move |&&(label, _)| label == c)
+                        .map(|&(_, target)| target)
+                }));
+                if targets.is_empty() {
+                    transitions.push(None);
+                    continue;
+                }
+                let count = states.len();
+                let target = *states.entry(targets.clone()).or_insert(count);
+                if target == count {
+                    if count == MAX_DFA_STATES {
+                        return None;
+                    }
+                    subsets.push(targets);
+                }
+                transitions.push(Some(target));
+            }
+            dfa.accepting.push(subset.contains(&nfa.accepting));
+            dfa.transitions.push(transitions);
+        }
+        Some(dfa)
+    }
+
+    pub fn states(&self) -> usize {
+        self.transitions.len()
+    }
+
+    fn next(&self, state: usize, c: char) -> Option<usize> {
+        let letter = self.alphabet.iter().position(|&letter| letter == c)?;
+        self.transitions[state][letter]
+    }
+
+    pub fn accepts(&self, message: &str) -> bool {
+        message
+            .chars()
+            .try_fold(0, |state, c| self.next(state, c))
+            .is_some_and(|state| self.accepting[state])
+    }
+
+    /// Every end of a match starting at `start` in `message`.
+    fn ends(&self, message: &[char], start: usize) -> Vec<usize> {
+        let mut ends = vec![];
+        let mut state = 0;
+        for (index, &c) in message.iter().enumerate().skip(start) {
+            state = match self.next(state, c) {
+                Some(state) => state,
+                None => break,
+            };
+            if self.accepting[state] {
+                ends.push(index + 1);
+            }
+        }
+        ends
+    }
+}
+
+/// Compiled form of a `RuleSet`, see `RuleSet::compile`.
+pub struct Matcher<'a> {
+    rule_set: &'a RuleSet,
+    start: usize,
+    /// Automata of the start rule if it is regular, otherwise of the regular rules used by the
+    /// recursive ones.
+    automata: HashMap<usize, Dfa>,
+}
+
+impl Matcher<'_> {
+    /// Whether the start rule is compiled to a single automaton.
+    pub fn is_regular(&self) -> bool {
+        self.automata.contains_key(&self.start)
+    }
+
+    pub fn matches(&self, message: &str) -> bool {
+        match self.automata.get(&self.start) {
+            Some(dfa) => dfa.accepts(message),
+            None => {
+                let message = message.chars().collect::<Vec<_>>();
+                Earley::new(self.rule_set, &self.automata, &message).matches(self.start)
+            }
+        }
+    }
+}
+
+/// Partial match of an alternative of `rule`, the first `dot` rules of the sequence matched the
+/// message from `origin` to the position of the item.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+struct Item {
+    rule: usize,
+    alternative: usize,
+    dot: usize,
+    origin: usize,
+}
+
+/// Earley recogniser, it handles any recursion since the rules never match an empty message.
+///
+/// The rules with an automaton are matched as a whole by it, like single characters.
+struct Earley<'a> {
+    rule_set: &'a RuleSet,
+    automata: &'a HashMap<usize, Dfa>,
+    message: &'a [char],
+    /// Items at each position of the message, in the order they were found.
+    items: Vec<Vec<Item>>,
+    seen: Vec<HashSet<Item>>,
+    /// Rules matched from an origin to each position.
+    completed: Vec<Vec<(usize, usize)>>,
+    completed_seen: Vec<HashSet<(usize, usize)>>,
+    /// Items at each position waiting for a rule to match from there.
+    waiting: Vec<HashMap<usize, Vec<Item>>>,
+}
+
+impl<'a> Earley<'a> {
+    fn new(rule_set: &'a RuleSet, automata: &'a HashMap<usize, Dfa>, message: &'a [char]) -> Self {
+        let positions = message.len() + 1;
+        Self {
+            rule_set,
+            automata,
+            message,
+            items: vec![vec![]; positions],
+            seen: vec![HashSet::new(); positions],
+            completed: vec![vec![]; positions],
+            completed_seen: vec![HashSet::new(); positions],
+            waiting: vec![HashMap::new(); positions],
+        }
+    }
+
+    fn sequence(&self, item: &Item) -> &'a [usize] {
+        match self.rule_set.rule(item.rule) {
+            Some(Rule::Sequences(sequences)) => &sequences[item.alternative],
+            _ => unreachable!("items are only made of sequences"),
+        }
+    }
+
+    fn add_item(&mut self, position: usize, item: Item) {
+        if self.seen[position].insert(item) {
+            self.items[position].push(item);
+        }
+    }
+
+    fn add_completed(&mut self, position: usize, rule: usize, origin: usize) {
+        if self.completed_seen[position].insert((rule, origin)) {
+            self.completed[position].push((rule, origin));
+        }
+    }
+
+    /// Starts the matches of `rule` at `position`.
+    fn predict(&mut self, rule: usize, position: usize) {
+        if let Some(dfa) = self.automata.get(&rule) {
+            for end in dfa.ends(self.message, position) {
+                self.add_completed(end, rule, position);
+            }
+            return;
+        }
+        match self.rule_set.rule(rule) {
+            Some(&Rule::Char(c)) if self.message.get(position) == Some(&c) => {
+                self.add_completed(position + 1, rule, position);
+            }
+            Some(Rule::Sequences(sequences)) => {
+                for alternative in 0..sequences.len() {
+                    self.add_item(
+                        position,
+                        Item {
+                            rule,
+                            alternative,
+                            dot: 0,
+                            origin: position,
+                        },
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Fills the chart, returns whether `start` matches the whole message.
+    fn matches(&mut self, start: usize) -> bool {
+        self.predict(start, 0);
+        for position in 0..self.items.len() {
+            let (mut item_index, mut completed_index) = (0, 0);
+            loop {
+                if let Some(&item) = self.items[position].get(item_index) {
+                    item_index += 1;
+                    match self.sequence(&item).get(item.dot) {
+                        Some(&next) => {
+                            let waiting = self.waiting[position].entry(next).or_default();
+                            waiting.push(item);
+                            // the first item waiting for `next` here predicts it
+                            if waiting.len() == 1 {
+                                self.predict(next, position);
+                            }
+                        }
+                        None => self.add_completed(position, item.rule, item.origin),
+                    }
+                } else if let Some(&(rule, origin)) = self.completed[position].get(completed_index)
+                {
+                    completed_index += 1;
+                    // the origin is before the position, all its waiting items are known
+                    let waiting = self.waiting[origin].get(&rule).cloned().unwrap_or_default();
+                    for item in waiting {
+                        self.add_item(
+                            position,
+                            Item {
+                                dot: item.dot + 1,
+                                ..item
+                            },
+                        );
+                    }
+                } else {
+                    break;
+                }
+            }
+        }
+        self.completed_seen[self.message.len()].contains(&(start, 0))
+    }
+
+    /// Derivation of `rule` from `start` to `end` in the filled chart, `stack` holds the spans
+    /// being derived so that cycles of single rules are not followed.
+    fn derive(
+        &self,
+        rule: usize,
+        start: usize,
+        end: usize,
+        stack: &mut Vec<(usize, usize, usize)>,
+    ) -> Option<Derivation> {
+        if !self.completed_seen[end].contains(&(rule, start)) || stack.contains(&(rule, start, end))
+        {
+            return None;
+        }
+        let sequences = match self.rule_set.rule(rule)? {
+            &Rule::Char(c) => return Some(Derivation::Char(rule, c)),
+            Rule::Sequences(sequences) => sequences,
+        };
+        stack.push((rule, start, end));
+        let derivation = (0..sequences.len()).find_map(|alternative| {
+            let item = Item {
+                rule,
+                alternative,
+                dot: sequences[alternative].len(),
+                origin: start,
+            };
+            self.derive_item(item, end, stack)
+                .map(|children| Derivation::Rule(rule, children))
+        });
+        stack.pop();
+        derivation
+    }
+
+    /// Derivations of the rules before the dot of `item`, which ends at `end`.
+    fn derive_item(
+        &self,
+        item: Item,
+        end: usize,
+        stack: &mut Vec<(usize, usize, usize)>,
+    ) -> Option<Vec<Derivation>> {
+        if !self.seen[end].contains(&item) {
+            return None;
+        }
+        if item.dot == 0 {
+            return Some(vec![]);
+        }
+        let last = self.sequence(&item)[item.dot - 1];
+        let previous = Item {
+            dot: item.dot - 1,
+            ..item
+        };
+        (item.origin..end).rev().find_map(|middle| {
+            let child = self.derive(last, middle, end, stack)?;
+            let mut children = self.derive_item(previous, middle, stack)?;
+            children.push(child);
+            Some(children)
+        })
+    }
+}
+
+/// How a message matches a rule, the rule numbers are the nodes and the characters the leaves.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Derivation {
+    Char(usize, char),
+    Rule(usize, Vec<Derivation>),
+}
+
+impl Derivation {
+    pub fn rule(&self) -> usize {
+        match self {
+            &Derivation::Char(rule, _) | &Derivation::Rule(rule, _) => rule,
+        }
+    }
+
+    /// The matched message.
+    pub fn text(&self) -> String {
+        match self {
+            &Derivation::Char(_, c) => c.to_string(),
+            Derivation::Rule(_, children) => children.iter().map(Derivation::text).collect(),
+        }
+    }
+}
+
+/// As nested lists, like `(0 (4 "a") (5 "b"))`.
+impl Display for Derivation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Derivation::Char(rule, c) => write!(f, "({} \"{}\")", rule, c),
+            Derivation::Rule(rule, children) => {
+                write!(f, "({}", rule)?;
+                for child in children {
+                    write!(f, " {}", child)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+pub fn solve(rule_set: &RuleSet, messages: &str) -> usize {
+    let matcher = rule_set.compile(0);
+    messages
+        .lines()
+        .filter(|message| matcher.matches(message))
+        .count()
+}
+
+pub struct Day19;
+
+impl Solution for Day19 {
+    type Input<'a> = (RuleSet, &'a str);
+    type Output1 = usize;
+    type Output2 = Result<usize, SolveError>;
+
+    const DAY: u8 = 19;
+    const TITLE: &'static str = "Monster Messages";
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        let mut input_parts = input.splitn(2, "\n\n");
+        let rules = input_parts.next().unwrap();
+        let messages = next_token(&mut input_parts, input, "expected messages after the rules")?;
+        Ok((RuleSet::new(rules)?, messages))
+    }
+
+    fn part_1((rule_set, messages): &Self::Input<'_>) -> usize {
+        solve(rule_set, messages)
+    }
+
+    fn part_2((rule_set, messages): &Self::Input<'_>) -> Result<usize, SolveError> {
+        let mut rule_set = rule_set.clone();
+        rule_set.update(LOOPING_RULES).map_err(|error| {
+            SolveError::NoSolution(format!(
+                "rule {} used by the looping rules is undefined",
+                error.token
+            ))
+        })?;
+        Ok(solve(&rule_set, messages))
+    }
 }
 
 #[cfg(test)]
@@ -97,9 +641,13 @@ mod tests {
         let rules = input_parts.next().unwrap();
         let messages = input_parts.next().unwrap();
 
-        let rule_set = RuleSet::new(rules, HashMap::new());
+        let rule_set = RuleSet::new(rules).unwrap();
 
         assert_eq!(solve(&rule_set, messages), 2);
+        let matcher = rule_set.compile(0);
+        assert!(matcher.is_regular());
+        assert!(matcher.matches("aaabab"));
+        assert!(!matcher.matches("aaaba"));
     }
 
     #[test]
@@ -109,14 +657,112 @@ mod tests {
         let rules = input_parts.next().unwrap();
         let messages = input_parts.next().unwrap();
 
-        let rule_set = RuleSet::new(
-            rules,
-            [("8", "42 | 42 8"), ("11", "42 31 | 42 11 31")]
-                .iter()
-                .copied()
-                .collect(),
-        );
+        let mut rule_set = RuleSet::new(rules).unwrap();
+        assert_eq!(solve(&rule_set, messages), 3);
 
+        rule_set.update(LOOPING_RULES).unwrap();
+        assert!(!rule_set.is_regular(0));
+        assert!(rule_set.is_regular(42));
+        assert!(!rule_set.compile(0).is_regular());
         assert_eq!(solve(&rule_set, messages), 12);
     }
+
+    #[test]
+    fn test_large_automata() {
+        // rule 0 matches up to 4096 `a`, its automata are too large to build
+        let mut input = (0..12)
+            .map(|rule| format!("{}: {} {} | {}\n", rule, rule + 1, rule + 1, rule + 1))
+            .collect::<String>();
+        input.push_str("12: \"a\"\n42: 12\n31: 12\n\naaaa\nb");
+        let parsed = Day19::parse(&input).unwrap();
+        let matcher = parsed.0.compile(0);
+        assert!(!matcher.is_regular());
+        assert!(matcher.matches("aaaaaaa"));
+        assert!(!matcher.matches("aab"));
+        assert_eq!(Day19::part_1(&parsed), 1);
+        assert_eq!(Day19::part_2(&parsed), Ok(1));
+    }
+
+    #[test]
+    fn test_missing_looping_rules() {
+        let input = "0: 1 1\n1: \"a\"\n\naa\nab";
+        let parsed = Day19::parse(input).unwrap();
+        assert_eq!(Day19::part_1(&parsed), 1);
+        assert_eq!(
+            Day19::part_2(&parsed),
+            Err(SolveError::NoSolution(
+                "rule 42 used by the looping rules is undefined".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let input = "0: 1 2\n1: \"a\"\n2: 1 | x";
+        assert_eq!(
+            RuleSet::new(input).err().unwrap(),
+            ParseError::new(3, 8, "x", "expected a rule number")
+        );
+        assert_eq!(
+            RuleSet::new("0: \"ab\"").err().unwrap(),
+            ParseError::new(1, 4, "\"ab\"", "expected a single character between quotes")
+        );
+    }
+
+    #[test]
+    fn test_recursion() {
+        // a^n b^n is not regular, the left recursion of 2 must not loop
+        let rule_set = RuleSet::new("0: 3 0 4 | 3 4\n2: 2 3 | 3\n3: \"a\"\n4: \"b\"").unwrap();
+        let matcher = rule_set.compile(0);
+        assert!(!matcher.is_regular());
+        assert!(matcher.matches("aaabbb"));
+        assert!(!matcher.matches("aaabb"));
+        assert!(!matcher.matches("abab"));
+        assert!(rule_set.compile(2).matches("a"));
+
+        // left recursion
+        let rule_set = RuleSet::new("0: 0 1 | 1 2\n1: \"a\"\n2: \"b\"").unwrap();
+        let matcher = rule_set.compile(0);
+        assert!(matcher.matches("abaaa"));
+        assert!(!matcher.matches("aaab"));
+        // cycle of single rules
+        let rule_set = RuleSet::new("0: 1 | 2 2\n1: 0 | 3\n2: \"a\"\n3: \"b\"").unwrap();
+        assert!(rule_set.compile(0).matches("aa"));
+        assert_eq!(
+            rule_set.derive(0, "b").unwrap().to_string(),
+            "(0 (1 (3 \"b\")))"
+        );
+    }
+
+    #[test]
+    fn test_undefined_rule() {
+        assert_eq!(
+            RuleSet::new("0: 1 5 | 2\n1: \"a\"\n2: 1").err().unwrap(),
+            ParseError::new(1, 6, "5", "undefined rule")
+        );
+        let mut rule_set = RuleSet::new("0: 1\n1: \"a\"").unwrap();
+        assert_eq!(
+            rule_set.update("0: 1 2").err().unwrap(),
+            ParseError::new(1, 6, "2", "undefined rule")
+        );
+        // the failed update changes nothing
+        assert_eq!(rule_set.rule(0), Some(&Rule::Sequences(vec![vec![1]])));
+        assert!(rule_set.update("2: \"b\"\n0: 1 2").is_ok());
+    }
+
+    #[test]
+    fn test_derive() {
+        let rule_set =
+            RuleSet::new("0: 4 1 5\n1: 2 3 | 3 2\n2: 4 4 | 5 5\n3: 4 5 | 5 4\n4: \"a\"\n5: \"b\"")
+                .unwrap();
+        let derivation = rule_set.derive(0, "abbbab").unwrap();
+        assert_eq!(
+            derivation.to_string(),
+            "(0 (4 \"a\") (1 (2 (5 \"b\") (5 \"b\")) (3 (5 \"b\") (4 \"a\"))) (5 \"b\"))"
+        );
+        assert_eq!(derivation.rule(), 0);
+        assert_eq!(derivation.text(), "abbbab");
+        assert_eq!(rule_set.derive(0, "bababa"), None);
+        assert_eq!(rule_set.derive(7, "a"), None);
+    }
 }
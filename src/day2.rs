@@ -1,5 +1,10 @@
+use crate::{
+    error::{next_token, parse_token, ParseError},
+    solution::Solution,
+};
+
 #[derive(Debug, PartialEq)]
-struct Policy {
+pub struct Policy {
     first: u8,
     second: u8,
     character: char,
@@ -7,23 +12,29 @@ struct Policy {
 }
 
 impl Policy {
-    fn new(policy: &str) -> Self {
+    pub fn new(policy: &str) -> Result<Self, ParseError> {
         let mut first_split = policy.splitn(2, '-');
-        let first = first_split.next().unwrap().parse().unwrap();
-        let mut second_split = first_split.next().unwrap().splitn(2, ' ');
-        let second = second_split.next().unwrap().parse().unwrap();
-        let mut third_split = second_split.next().unwrap().splitn(2, ": ");
-        let character = third_split.next().unwrap().parse().unwrap();
-        let password = third_split.next().unwrap().to_owned();
-        Policy {
+        let first = parse_position(policy, first_split.next().unwrap())?;
+        let rest = next_token(&mut first_split, policy, "expected `-`")?;
+        let mut second_split = rest.splitn(2, ' ');
+        let second = parse_position(policy, second_split.next().unwrap())?;
+        let rest = next_token(&mut second_split, policy, "expected a space")?;
+        let mut third_split = rest.splitn(2, ": ");
+        let character = parse_token(
+            policy,
+            third_split.next().unwrap(),
+            "expected a single character",
+        )?;
+        let password = next_token(&mut third_split, policy, "expected `: `")?.to_owned();
+        Ok(Policy {
             first,
             second,
             character,
             password,
-        }
+        })
     }
 
-    fn is_valid_1(&self) -> bool {
+    pub fn is_valid_1(&self) -> bool {
         let char_count = self
             .password
             .chars()
@@ -32,39 +43,61 @@ impl Policy {
         char_count >= self.first as usize && char_count <= self.second as usize
     }
 
-    fn is_valid_2(&self) -> bool {
-        self.password.chars().nth(self.first as usize - 1).unwrap() == self.character
-            && self.password.chars().nth(self.second as usize - 1).unwrap() == self.character
+    pub fn is_valid_2(&self) -> bool {
+        self.password.chars().nth(self.first as usize - 1) == Some(self.character)
+            && self.password.chars().nth(self.second as usize - 1) == Some(self.character)
+    }
+}
+
+fn parse_position(policy: &str, token: &str) -> Result<u8, ParseError> {
+    match parse_token(policy, token, "expected a number")? {
+        0 => Err(ParseError::at(policy, token, "positions start at 1")),
+        position => Ok(position),
     }
 }
 
-fn parse_input(input: &str) -> Vec<Policy> {
-    input.lines().fold(vec![], |mut acc, line| {
-        acc.push(Policy::new(line));
-        acc
-    })
+pub fn parse_input(input: &str) -> Result<Vec<Policy>, ParseError> {
+    input
+        .lines()
+        .map(|line| Policy::new(line).map_err(|error| error.within(input, line)))
+        .collect()
 }
 
-fn solve_1(policies: &Vec<Policy>) -> u32 {
+pub fn solve_1(policies: &[Policy]) -> u32 {
     policies.iter().fold(
         0,
         |acc, policy| if policy.is_valid_1() { acc + 1 } else { acc },
     )
 }
 
-fn solve_2(policies: &Vec<Policy>) -> u32 {
+pub fn solve_2(policies: &[Policy]) -> u32 {
     policies.iter().fold(
         0,
         |acc, policy| if policy.is_valid_2() { acc + 1 } else { acc },
     )
 }
 
-pub fn run() {
-    let timer = std::time::Instant::now();
-    let input = std::fs::read_to_string("inputs/day2").unwrap();
-    let policies = parse_input(&input);
-    println!("day 2 solution 1 : {}, {}us", solve_1(&policies), timer.elapsed().as_micros());
-    println!("day 2 solution 2 : {}, {}us", solve_2(&policies), timer.elapsed().as_micros());
+pub struct Day2;
+
+impl Solution for Day2 {
+    type Input<'a> = Vec<Policy>;
+    type Output1 = u32;
+    type Output2 = u32;
+
+    const DAY: u8 = 2;
+    const TITLE: &'static str = "Password Philosophy";
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(policies: &Self::Input<'_>) -> u32 {
+        solve_1(policies)
+    }
+
+    fn part_2(policies: &Self::Input<'_>) -> u32 {
+        solve_2(policies)
+    }
 }
 
 #[cfg(test)]
@@ -74,7 +107,7 @@ mod tests {
     #[test]
     fn test_parse_input() {
         assert_eq!(
-            parse_input("1-3 a: abcde"),
+            parse_input("1-3 a: abcde").unwrap(),
             vec![Policy {
                 first: 1,
                 second: 3,
@@ -86,23 +119,29 @@ mod tests {
 
     #[test]
     fn test_policy_validity_1() {
-        assert!(Policy::new("1-3 a: abcde").is_valid_1());
+        assert!(Policy::new("1-3 a: abcde").unwrap().is_valid_1());
     }
 
     #[test]
     fn test_solve_1() {
         let input = "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc";
-        assert_eq!(solve_1(&parse_input(input)), 2);
+        assert_eq!(solve_1(&parse_input(input).unwrap()), 2);
     }
 
     #[test]
     fn test_policy_validity_2() {
-        assert!(!Policy::new("1-3 a: abcde").is_valid_2());
+        assert!(!Policy::new("1-3 a: abcde").unwrap().is_valid_2());
     }
 
     #[test]
     fn test_solve_2() {
         let input = "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc";
-        assert_eq!(solve_2(&parse_input(input)), 1);
+        assert_eq!(solve_2(&parse_input(input).unwrap()), 1);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = parse_input("1-3 a: abcde\n1-x b: cdefg").err().unwrap();
+        assert_eq!(error, ParseError::new(2, 3, "x", "expected a number"));
     }
 }
@@ -1,349 +1,690 @@
+use crate::{
+    error::{expect_chars, next_token, parse_token, ParseError, SolveError},
+    netpbm::{Image, Rgb, DIGIT_HEIGHT},
+    solution::Solution,
+};
 use std::{collections::HashMap, fmt::Display};
 
-struct Tile {
+#[derive(Clone)]
+pub struct Tile {
     id: u16,
     pixels: Vec<Vec<char>>,
-    borders: [String; 4],
 }
 
 impl Tile {
-    fn new(input: &str) -> Self {
+    pub fn new(input: &str) -> Result<Self, ParseError> {
         let mut parts = input.splitn(2, '\n');
-        let id = parts
-            .next()
-            .unwrap()
-            .trim_start_matches("Tile ")
-            .trim_end_matches(':')
-            .parse::<u16>()
-            .unwrap();
-        let pixels = parts
-            .next()
-            .unwrap()
-            .lines()
-            .map(|line| line.chars().collect::<Vec<_>>())
-            .collect::<Vec<_>>();
-        let borders = [
-            // top
-            pixels[0].iter().collect(),
-            // right
-            pixels.iter().map(|line| line[line.len() - 1]).collect(),
-            // bottom
-            pixels[pixels.len() - 1].iter().collect(),
-            // left
-            pixels.iter().map(|line| line[0]).collect(),
-        ];
-        Self {
-            id,
-            pixels,
-            borders,
+        let header = parts.next().unwrap();
+        let id = header
+            .strip_prefix("Tile ")
+            .and_then(|header| header.strip_suffix(':'))
+            .ok_or_else(|| ParseError::at(input, header, "expected `Tile <id>:`"))?;
+        let id = parse_token(input, id, "expected a tile id")?;
+        let lines = next_token(&mut parts, input, "expected the tile pixels")?.lines();
+        let mut pixels = vec![];
+        for line in lines {
+            expect_chars(input, line, ".#")?;
+            if line.chars().count() != pixels.first().map_or(line.len(), Vec::len) {
+                return Err(ParseError::at(
+                    input,
+                    line,
+                    "every row of the tile should have the same length",
+                ));
+            }
+            pixels.push(line.chars().collect::<Vec<_>>());
         }
+        if pixels.len() < 2 || pixels.len() != pixels[0].len() {
+            return Err(ParseError::at(input, header, "expected a square tile"));
+        }
+        Ok(Self { id, pixels })
     }
 
-    fn try_assemble(&self, other: &mut Self) -> Option<usize> {
-        let mut found = None;
-        'look: for (index, border) in self.borders.iter().enumerate() {
-            for (other_index, other_border) in other.borders.iter().enumerate() {
-                if border == other_border
-                    || border == &other_border.chars().rev().collect::<String>()
-                {
-                    found = Some((index, other_index));
-                    break 'look;
-                }
-            }
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+
+    pub fn size(&self) -> usize {
+        self.pixels.len()
+    }
+
+    /// The tile seen in another orientation, without copying it.
+    pub fn view(&self, orientation: Orientation) -> TileView<'_> {
+        TileView {
+            tile: self,
+            orientation,
+            source: orientation.inverse(),
         }
-        if let Some((index, other_index)) = found {
-            // println!("before\n{}", other);
-            match (4 + other_index - index) % 4 {
-                0 => {
-                    if index % 2 == 0 {
-                        other.flip_vert();
-                    } else {
-                        other.flip_hor();
-                    }
-                }
-                1 => {
-                    other.rotate_right();
-                }
-                2 => {}
-                3 => {
-                    other.rotate_right();
-                    if index % 2 == 0 {
-                        other.flip_vert();
-                    } else {
-                        other.flip_hor();
+    }
+
+    pub fn oriented(&self, orientation: Orientation) -> Tile {
+        self.view(orientation).to_tile()
+    }
+
+    /// Every match of the pattern in any orientation, the matches can overlap, and the number
+    /// of `#` left outside of them.
+    pub fn search(&self, pattern: &Pattern) -> Search {
+        let size = self.size();
+        let mut matches = vec![];
+        let mut masked = vec![vec![false; size]; size];
+        for (orientation, oriented) in pattern.orientations() {
+            if oriented.height > size || oriented.width > size {
+                continue;
+            }
+            for row in 0..=size - oriented.height {
+                for column in 0..=size - oriented.width {
+                    if oriented
+                        .cells
+                        .iter()
+                        .all(|&(i, j)| self.pixels[row + i][column + j] == '#')
+                    {
+                        for &(i, j) in &oriented.cells {
+                            masked[row + i][column + j] = true;
+                        }
+                        matches.push(Match {
+                            row,
+                            column,
+                            orientation,
+                        });
                     }
                 }
-                _ => {}
             }
-            if other.borders[(index + 2) % 4] != self.borders[index] {
-                if index % 2 == 0 {
-                    other.flip_hor();
-                } else {
-                    other.flip_vert();
-                }
+        }
+        let roughness = self
+            .pixels
+            .iter()
+            .flatten()
+            .zip(masked.iter().flatten())
+            .filter(|&(&pixel, &masked)| pixel == '#' && !masked)
+            .count();
+        Search { matches, roughness }
+    }
+
+    /// One image pixel per tile pixel.
+    pub fn to_image(&self) -> Image {
+        self.view(Orientation::IDENTITY).to_image()
+    }
+
+    /// The image with the pixels of every match of the pattern highlighted.
+    pub fn highlight(&self, pattern: &Pattern) -> Image {
+        let mut image = self.to_image();
+        for found in self.search(pattern).matches {
+            for &(i, j) in &pattern.oriented(found.orientation).cells {
+                image.set(found.column + j, found.row + i, HIGHLIGHT);
             }
-            // println!("after\n{}", other);
-            return Some(index);
         }
-        None
-    }
-
-    fn rotate_right(&mut self) {
-        self.borders.rotate_right(1);
-        self.borders[0] = self.borders[0].chars().rev().collect();
-        self.borders[2] = self.borders[2].chars().rev().collect();
-        let mut lines = vec![];
-        for i in 0..self.pixels.len() {
-            let mut line = vec![];
-            for j in 0..self.pixels[i].len() {
-                line.push(self.pixels[self.pixels.len() - 1 - j][i]);
+        image
+    }
+}
+
+impl Display for Tile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "id: {}", self.id)?;
+        for line in &self.pixels {
+            for pixel in line {
+                write!(f, "{}", pixel)?;
             }
-            lines.push(line);
+            writeln!(f)?;
         }
-        self.pixels = lines;
+        Ok(())
     }
+}
 
-    fn flip_vert(&mut self) {
-        self.borders.swap(0, 2);
-        self.borders[1] = self.borders[1].chars().rev().collect();
-        self.borders[3] = self.borders[3].chars().rev().collect();
-        let length = self.pixels.len();
-        for i in 0..length / 2 {
-            self.pixels.swap(i, length - 1 - i);
+/// Symmetry of a square, an element of the dihedral group D4 : the pixels are mirrored left to
+/// right if `flipped`, then rotated clockwise by `rotation` quarter turns.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Orientation {
+    flipped: bool,
+    rotation: u8,
+}
+
+impl Orientation {
+    pub const IDENTITY: Self = Self::new(false, 0);
+    /// The rotations, then the rotations of the flipped square.
+    pub const ALL: [Self; 8] = [
+        Self::new(false, 0),
+        Self::new(false, 1),
+        Self::new(false, 2),
+        Self::new(false, 3),
+        Self::new(true, 0),
+        Self::new(true, 1),
+        Self::new(true, 2),
+        Self::new(true, 3),
+    ];
+
+    pub const fn new(flipped: bool, rotation: u8) -> Self {
+        Self {
+            flipped,
+            rotation: rotation % 4,
         }
     }
 
-    fn flip_hor(&mut self) {
-        self.borders.swap(1, 3);
-        self.borders[0] = self.borders[0].chars().rev().collect();
-        self.borders[2] = self.borders[2].chars().rev().collect();
-        for line in self.pixels.iter_mut() {
-            let length = line.len();
-            for i in 0..length / 2 {
-                line.swap(i, length - 1 - i);
-            }
+    pub fn flipped(&self) -> bool {
+        self.flipped
+    }
+
+    pub fn rotation(&self) -> u8 {
+        self.rotation
+    }
+
+    /// Position in `ALL`.
+    fn index(&self) -> usize {
+        4 * self.flipped as usize + self.rotation as usize
+    }
+
+    /// `self` followed by `other`.
+    pub fn then(self, other: Self) -> Self {
+        // a flip reverses the rotations made before it
+        let rotation = if other.flipped {
+            4 - self.rotation
+        } else {
+            self.rotation
+        };
+        Self::new(self.flipped != other.flipped, rotation + other.rotation)
+    }
+
+    pub fn inverse(self) -> Self {
+        if self.flipped {
+            self
+        } else {
+            Self::new(false, 4 - self.rotation)
         }
     }
 
-    fn count_sea_monsters(&self) -> u32 {
-        let mut count = 0;
-        for i in 1..self.pixels.len() - 1 {
-            for j in 0..=self.pixels[i].len() - 20 {
-                if self.pixels[i][j] == '#'
-                    && self.pixels[i][j + 5] == '#'
-                    && self.pixels[i][j + 6] == '#'
-                    && self.pixels[i][j + 11] == '#'
-                    && self.pixels[i][j + 12] == '#'
-                    && self.pixels[i][j + 17] == '#'
-                    && self.pixels[i][j + 18] == '#'
-                    && self.pixels[i][j + 19] == '#'
-                    && self.pixels[i - 1][j + 18] == '#'
-                    && self.pixels[i + 1][j + 1] == '#'
-                    && self.pixels[i + 1][j + 4] == '#'
-                    && self.pixels[i + 1][j + 7] == '#'
-                    && self.pixels[i + 1][j + 10] == '#'
-                    && self.pixels[i + 1][j + 13] == '#'
-                    && self.pixels[i + 1][j + 16] == '#'
-                {
-                    count += 1;
+    /// Where the cell at `row` and `column` of a grid of `height` by `width` cells ends up.
+    pub fn apply(
+        self,
+        (mut row, mut column): (usize, usize),
+        (mut height, mut width): (usize, usize),
+    ) -> (usize, usize) {
+        if self.flipped {
+            column = width - 1 - column;
+        }
+        for _ in 0..self.rotation {
+            (row, column) = (column, height - 1 - row);
+            (height, width) = (width, height);
+        }
+        (row, column)
+    }
+}
+
+impl Display for Orientation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.flipped, self.rotation) {
+            (false, 0) => write!(f, "identity"),
+            (false, rotation) => write!(f, "rotated {}", 90 * rotation as u32),
+            (true, 0) => write!(f, "flipped"),
+            (true, rotation) => write!(f, "flipped and rotated {}", 90 * rotation as u32),
+        }
+    }
+}
+
+/// A tile in some orientation, each pixel is read from the tile when needed.
+#[derive(Clone, Copy)]
+pub struct TileView<'a> {
+    tile: &'a Tile,
+    orientation: Orientation,
+    /// Inverse of the orientation, mapping the pixels of the view to the ones of the tile.
+    source: Orientation,
+}
+
+impl TileView<'_> {
+    pub fn id(&self) -> u16 {
+        self.tile.id
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    pub fn size(&self) -> usize {
+        self.tile.size()
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> char {
+        let size = self.size();
+        let (row, column) = self.source.apply((row, column), (size, size));
+        self.tile.pixels[row][column]
+    }
+
+    /// Top and bottom borders from left to right, right and left borders from top to bottom, one
+    /// bit per pixel set.
+    fn borders(&self) -> [u64; 4] {
+        let last = self.size() - 1;
+        let code = |pixel: &dyn Fn(usize) -> char| {
+            (0..=last).fold(0, |code, index| code << 1 | (pixel(index) == '#') as u64)
+        };
+        [
+            code(&|column| self.get(0, column)),
+            code(&|row| self.get(row, last)),
+            code(&|column| self.get(last, column)),
+            code(&|row| self.get(row, 0)),
+        ]
+    }
+
+    pub fn to_tile(&self) -> Tile {
+        let size = self.size();
+        Tile {
+            id: self.id(),
+            pixels: (0..size)
+                .map(|row| (0..size).map(|column| self.get(row, column)).collect())
+                .collect(),
+        }
+    }
+
+    pub fn to_image(&self) -> Image {
+        let mut image = Image::new(self.size(), self.size(), WATER);
+        for y in 0..self.size() {
+            for x in 0..self.size() {
+                if self.get(y, x) == '#' {
+                    image.set(x, y, WAVE);
                 }
             }
         }
-        count
+        image
     }
 }
 
-impl Display for Tile {
+const WATER: Rgb = [16, 42, 92];
+const WAVE: Rgb = [222, 230, 240];
+const HIGHLIGHT: Rgb = [40, 200, 80];
+const SEAM: Rgb = [128, 128, 128];
+const TILE_ID: Rgb = [230, 50, 40];
+
+pub const SEA_MONSTER: &str = "                  # \n#    ##    ##    ###\n #  #  #  #  #  #   ";
+
+/// Shape searched in an image, `#` are the pixels it requires and anything else is ignored.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pattern {
+    height: usize,
+    width: usize,
+    /// Sorted positions of the `#`, relative to the top left corner of the pattern.
+    cells: Vec<(usize, usize)>,
+}
+
+impl Pattern {
+    /// Parses the pattern, the rows and columns without any `#` around it are dropped.
+    pub fn new(input: &str) -> Result<Self, ParseError> {
+        let cells = input
+            .lines()
+            .enumerate()
+            .flat_map(|(i, line)| {
+                line.chars()
+                    .enumerate()
+                    .filter(|&(_, c)| c == '#')
+                    .map(move |(j, _)| (i, j))
+            })
+            .collect::<Vec<_>>();
+        if cells.is_empty() {
+            return Err(ParseError::at(
+                input,
+                &input[input.len()..],
+                "expected at least one `#`",
+            ));
+        }
+        let top = cells.iter().map(|&(i, _)| i).min().unwrap();
+        let left = cells.iter().map(|&(_, j)| j).min().unwrap();
+        Ok(Self::from_cells(
+            cells.iter().map(|&(i, j)| (i - top, j - left)).collect(),
+        ))
+    }
+
+    pub fn sea_monster() -> Self {
+        Self::new(SEA_MONSTER).unwrap()
+    }
+
+    fn from_cells(mut cells: Vec<(usize, usize)>) -> Self {
+        cells.sort_unstable();
+        Self {
+            height: cells.iter().map(|&(i, _)| i + 1).max().unwrap(),
+            width: cells.iter().map(|&(_, j)| j + 1).max().unwrap(),
+            cells,
+        }
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn oriented(&self, orientation: Orientation) -> Self {
+        Self::from_cells(
+            self.cells
+                .iter()
+                .map(|&cell| orientation.apply(cell, (self.height, self.width)))
+                .collect(),
+        )
+    }
+
+    /// The orientations giving distinct patterns.
+    fn orientations(&self) -> Vec<(Orientation, Pattern)> {
+        let mut orientations: Vec<(Orientation, Pattern)> = vec![];
+        for orientation in Orientation::ALL {
+            let pattern = self.oriented(orientation);
+            if orientations.iter().all(|(_, other)| *other != pattern) {
+                orientations.push((orientation, pattern));
+            }
+        }
+        orientations
+    }
+}
+
+/// Position of the top left corner of a pattern found in an image, and the orientation of the
+/// pattern.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Match {
+    pub row: usize,
+    pub column: usize,
+    pub orientation: Orientation,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Search {
+    pub matches: Vec<Match>,
+    /// Number of `#` which are not part of a match.
+    pub roughness: usize,
+}
+
+/// How `Picture::to_image` draws the picture.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RenderOptions {
+    /// Size of the square drawn for each pixel.
+    pub scale: usize,
+    /// Separates the tiles with a line.
+    pub seams: bool,
+    /// Writes the id of every tile in its top left corner, the scale must be large enough for
+    /// the ids to fit.
+    pub ids: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            scale: 1,
+            seams: false,
+            ids: false,
+        }
+    }
+}
+
+/// Why the tiles cannot be assembled into a single picture.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AssemblyError {
+    /// The number of tiles is not a square.
+    NotSquare(usize),
+    /// The tile has not the size of the first one.
+    SizeMismatch(u16),
+    /// The tile borders do not fit in the edge codes.
+    TooLarge(u16),
+    NoAssembly,
+    MultipleAssemblies,
+}
+
+impl Display for AssemblyError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "id: {}", self.id)?;
-        for line in &self.pixels {
-            for pixel in line {
-                write!(f, "{}", pixel)?;
+        match self {
+            AssemblyError::NotSquare(count) => {
+                write!(f, "{} tiles cannot make a square picture", count)
+            }
+            AssemblyError::SizeMismatch(id) => {
+                write!(f, "tile {} is not the size of the other tiles", id)
+            }
+            AssemblyError::TooLarge(id) => {
+                write!(f, "tile {} is larger than {} pixels", id, MAX_TILE_SIZE)
+            }
+            AssemblyError::NoAssembly => write!(f, "the tiles do not fit together"),
+            AssemblyError::MultipleAssemblies => {
+                write!(f, "the tiles fit together in several ways")
             }
-            write!(f, "\n")?;
         }
-        Ok(())
     }
 }
 
-struct Picture {
-    tiles: HashMap<(i8, i8), Tile>,
-    boundaries: ((i8, i8), (i8, i8)),
+/// Largest tile whose borders fit in an edge code.
+const MAX_TILE_SIZE: usize = 64;
+
+/// Same code for a border read in both directions.
+fn canonical(code: u64, size: usize) -> u64 {
+    code.min(code.reverse_bits() >> (64 - size))
+}
+
+/// Tiles placed on the grid, as the tile indices and their orientations.
+type Placement = Vec<(usize, Orientation)>;
+
+/// Backtracking search of the placements, row by row, the candidates for a position being the
+/// tiles with the border of the tile on the left, or above at the start of a row.
+struct Assembler {
+    width: usize,
+    /// Pixels on a side of the tiles.
+    size: usize,
+    /// Borders of every tile, in each orientation of `Orientation::ALL`.
+    borders: Vec<Vec<[u64; 4]>>,
+    /// Tiles having a border, by canonical code.
+    edges: HashMap<u64, Vec<usize>>,
+    used: Vec<bool>,
+    placement: Placement,
+    solutions: Vec<Placement>,
 }
 
-impl Picture {
-    fn new(mut tiles: Vec<Tile>) -> Self {
-        let mut picture = HashMap::<(i8, i8), Tile>::new();
-        let mut boundaries = ((0i8, 0i8), (0i8, 0i8));
-
-        let first_tile = tiles.pop().unwrap();
-        picture.insert((0, 0), first_tile);
-
-        let mut to_process = vec![(0i8, 0i8)];
-        while to_process.len() > 0 {
-            let mut new_to_process = vec![];
-            for coordinates in &to_process {
-                let tile = picture.get(&coordinates).unwrap();
-                // println!(
-                //     "tile to process\n{:?}\n{}\n{:?}",
-                //     coordinates, tile, tile.borders
-                // );
-                let mut matching_tiles = vec![];
-                for (index, other_tile) in tiles.iter_mut().enumerate() {
-                    if let Some(direction) = tile.try_assemble(other_tile) {
-                        matching_tiles.push((index, direction));
-                        if matching_tiles.len() == 4 {
-                            break;
-                        }
-                    }
+impl Assembler {
+    /// Each assembly has 8 solutions, one per symmetry of the whole picture, so the search stops
+    /// once a 9th one shows there is more than one assembly.
+    const MAX_SOLUTIONS: usize = 9;
+
+    fn search(&mut self) {
+        let position = self.placement.len();
+        if position == self.borders.len() {
+            self.solutions.push(self.placement.clone());
+            return;
+        }
+        let (row, column) = (position / self.width, position % self.width);
+        let left = (column > 0).then(|| self.border(position - 1, 1));
+        let top = (row > 0).then(|| self.border(position - self.width, 2));
+        let candidates = match left.or(top) {
+            Some(border) => self.edges.get(&border.1).cloned().unwrap_or_default(),
+            None => (0..self.borders.len()).collect(),
+        };
+        for tile in candidates {
+            if self.used[tile] {
+                continue;
+            }
+            for orientation in Orientation::ALL {
+                let borders = self.borders[tile][orientation.index()];
+                if left.is_some_and(|(left, _)| borders[3] != left)
+                    || top.is_some_and(|(top, _)| borders[0] != top)
+                {
+                    continue;
                 }
-                for &(index, direction) in matching_tiles.iter().rev() {
-                    let matching_tile = tiles.remove(index);
-                    let (x, y) = match direction {
-                        0 => (coordinates.0 - 1, coordinates.1),
-                        1 => (coordinates.0, coordinates.1 + 1),
-                        2 => (coordinates.0 + 1, coordinates.1),
-                        3 => (coordinates.0, coordinates.1 - 1),
-                        _ => panic!("invalid direction"),
-                    };
-                    // println!(
-                    //     "matching tile\n{:?}\n{}\n{:?}",
-                    //     (x, y),
-                    //     matching_tile,
-                    //     matching_tile.borders
-                    // );
-                    picture.insert((x, y), matching_tile);
-                    new_to_process.push((x, y));
-                    boundaries = (
-                        (
-                            if x < boundaries.0 .0 {
-                                x
-                            } else {
-                                boundaries.0 .0
-                            },
-                            if y < boundaries.0 .1 {
-                                y
-                            } else {
-                                boundaries.0 .1
-                            },
-                        ),
-                        (
-                            if x > boundaries.1 .0 {
-                                x
-                            } else {
-                                boundaries.1 .0
-                            },
-                            if y > boundaries.1 .1 {
-                                y
-                            } else {
-                                boundaries.1 .1
-                            },
-                        ),
-                    )
+                self.used[tile] = true;
+                self.placement.push((tile, orientation));
+                self.search();
+                self.placement.pop();
+                self.used[tile] = false;
+                if self.solutions.len() >= Self::MAX_SOLUTIONS {
+                    return;
                 }
             }
-            to_process = new_to_process;
         }
+    }
 
-        Self {
-            tiles: picture,
-            boundaries,
+    /// Border `side` of the tile placed at `position`, along with its canonical code.
+    fn border(&self, position: usize, side: usize) -> (u64, u64) {
+        let (tile, orientation) = self.placement[position];
+        let border = self.borders[tile][orientation.index()][side];
+        (border, canonical(border, self.size))
+    }
+}
+
+/// The tiles assembled on a square grid, oriented so their borders match.
+pub struct Picture {
+    /// Row by row, with the orientation of the tile in the picture.
+    tiles: Vec<(Tile, Orientation)>,
+    width: usize,
+}
+
+impl Picture {
+    pub fn new(tiles: Vec<Tile>) -> Result<Self, AssemblyError> {
+        let width = (0..=tiles.len())
+            .find(|width| width * width >= tiles.len())
+            .unwrap();
+        if width * width != tiles.len() || tiles.is_empty() {
+            return Err(AssemblyError::NotSquare(tiles.len()));
+        }
+        let size = tiles[0].size();
+        if let Some(tile) = tiles.iter().find(|tile| tile.size() != size) {
+            return Err(AssemblyError::SizeMismatch(tile.id));
+        }
+        if size > MAX_TILE_SIZE {
+            return Err(AssemblyError::TooLarge(tiles[0].id));
+        }
+
+        let borders = tiles
+            .iter()
+            .map(|tile| {
+                Orientation::ALL
+                    .iter()
+                    .map(|&orientation| tile.view(orientation).borders())
+                    .collect()
+            })
+            .collect::<Vec<Vec<_>>>();
+        let mut edges = HashMap::<_, Vec<_>>::new();
+        for (tile, borders) in borders.iter().enumerate() {
+            for &border in &borders[0] {
+                let tiles = edges.entry(canonical(border, size)).or_default();
+                if tiles.last() != Some(&tile) {
+                    tiles.push(tile);
+                }
+            }
+        }
+        let mut assembler = Assembler {
+            width,
+            size,
+            borders,
+            edges,
+            used: vec![false; tiles.len()],
+            placement: vec![],
+            solutions: vec![],
+        };
+        assembler.search();
+        match assembler.solutions.len() {
+            0 => Err(AssemblyError::NoAssembly),
+            8 => Ok(Self {
+                tiles: assembler.solutions[0]
+                    .iter()
+                    .map(|&(tile, orientation)| (tiles[tile].clone(), orientation))
+                    .collect(),
+                width,
+            }),
+            _ => Err(AssemblyError::MultipleAssemblies),
         }
     }
 
-    fn get_picture(&self) -> Tile {
-        let mut tiles = vec![];
-        for x in (self.boundaries.0 .0)..=(self.boundaries.1 .0) {
-            let mut row = vec![];
-            for y in (self.boundaries.0 .1)..=(self.boundaries.1 .1) {
-                let tile = self.tiles.get(&(x, y)).unwrap();
-                row.push(tile);
+    /// Number of tiles on a side.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The tile at `row` and `column`, as placed in the picture.
+    pub fn tile(&self, row: usize, column: usize) -> TileView<'_> {
+        let (tile, orientation) = &self.tiles[row * self.width + column];
+        tile.view(*orientation)
+    }
+
+    /// The tiles with their borders, each pixel drawn as a square of `options.scale` pixels.
+    pub fn to_image(&self, options: &RenderOptions) -> Image {
+        let tile_size = self.tiles[0].0.size() * options.scale;
+        let step = tile_size + options.seams as usize;
+        let side = self.width * step - options.seams as usize;
+        let mut image = Image::new(side, side, SEAM);
+        for row in 0..self.width {
+            for column in 0..self.width {
+                let tile = self.tile(row, column);
+                let (x, y) = (column * step, row * step);
+                let tile_image = tile.to_image().scaled(options.scale);
+                for j in 0..tile_size {
+                    for i in 0..tile_size {
+                        image.set(x + i, y + j, tile_image.get(i, j));
+                    }
+                }
+                if options.ids && tile_size > DIGIT_HEIGHT + 1 {
+                    image.draw_number(x + 1, y + 1, x + tile_size - 1, tile.id() as u64, TILE_ID);
+                }
             }
-            tiles.push(row);
         }
+        image
+    }
 
-        let tile_height = tiles[0][0].pixels.len();
-        let tile_width = tiles[0][0].pixels[0].len();
+    /// The tiles without their borders.
+    pub fn get_picture(&self) -> Tile {
+        let inner = self.tiles[0].0.size() - 2;
         let mut pixels = vec![];
-        for row_index in 0..tiles.len() * (tile_height - 2) {
+        for row_index in 0..self.width * inner {
             let mut row = vec![];
-            for column_index in 0..tiles[row_index / (tile_height - 2)].len() * (tile_width - 2) {
+            for column_index in 0..self.width * inner {
                 row.push(
-                    tiles[row_index / (tile_height - 2)][column_index / (tile_width - 2)].pixels
-                        [row_index % (tile_height - 2) + 1][column_index % (tile_width - 2) + 1],
+                    self.tile(row_index / inner, column_index / inner)
+                        .get(row_index % inner + 1, column_index % inner + 1),
                 )
             }
             pixels.push(row);
         }
-
-        Tile {
-            pixels,
-            borders: [String::new(), String::new(), String::new(), String::new()],
-            id: 0,
-        }
+        Tile { pixels, id: 0 }
     }
 }
 
-fn parse_input(input: &str) -> Vec<Tile> {
-    input.split("\n\n").map(Tile::new).collect()
+pub fn parse_input(input: &str) -> Result<Vec<Tile>, ParseError> {
+    input
+        .split("\n\n")
+        .map(|tile| Tile::new(tile).map_err(|error| error.within(input, tile)))
+        .collect()
 }
 
-fn solve_1(picture: &Picture) -> u64 {
-    let ((xmin, ymin), (xmax, ymax)) = picture.boundaries;
-    picture.tiles.get(&(xmin, ymin)).unwrap().id as u64
-        * picture.tiles.get(&(xmin, ymax)).unwrap().id as u64
-        * picture.tiles.get(&(xmax, ymax)).unwrap().id as u64
-        * picture.tiles.get(&(xmax, ymin)).unwrap().id as u64
+fn assemble(tiles: &[Tile]) -> Result<Picture, SolveError> {
+    Picture::new(tiles.to_vec()).map_err(|error| SolveError::NoSolution(error.to_string()))
 }
 
-fn solve_2(mut tile: Tile) -> u32 {
-    let total = tile.pixels.iter().fold(0u32, |acc, curr| {
-        acc + curr
-            .iter()
-            .fold(0, |acc2, curr2| if *curr2 == '#' { acc2 + 1 } else { acc2 })
-    });
-    let mut sea_monsters = 0;
-    'transform: for _ in 0..2 {
-        for _ in 0..4 {
-            sea_monsters = tile.count_sea_monsters();
-            if sea_monsters != 0 {
-                break 'transform;
-            }
-            tile.rotate_right();
-        }
-        tile.flip_hor();
-    }
-    total - (sea_monsters * 15)
+pub fn solve_1(picture: &Picture) -> u64 {
+    let last = picture.width - 1;
+    [(0, 0), (0, last), (last, 0), (last, last)]
+        .iter()
+        .map(|&(row, column)| picture.tile(row, column).id() as u64)
+        .product()
 }
 
-pub fn run() {
-    // try to find four tiles that have 2 side that don't match other tiles
-    let timer = std::time::Instant::now();
-    let input = std::fs::read_to_string("inputs/day20").unwrap();
-    let tiles = parse_input(&input);
-    let picture = Picture::new(tiles);
-    println!(
-        "day 20 solution 1 : {}, {}us",
-        solve_1(&picture),
-        timer.elapsed().as_micros()
-    );
-    let mut picture = picture.get_picture();
-    picture.flip_hor();
-    picture.flip_vert();
-    println!(
-        "day 20 solution 2 : {}, {}us",
-        solve_2(picture),
-        timer.elapsed().as_micros()
-    );
+pub fn solve_2(image: &Tile) -> usize {
+    image.search(&Pattern::sea_monster()).roughness
+}
+
+pub struct Day20;
+
+impl Solution for Day20 {
+    type Input<'a> = Vec<Tile>;
+    type Output1 = Result<u64, SolveError>;
+    type Output2 = Result<usize, SolveError>;
+
+    const DAY: u8 = 20;
+    const TITLE: &'static str = "Jurassic Jigsaw";
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(tiles: &Self::Input<'_>) -> Result<u64, SolveError> {
+        Ok(solve_1(&assemble(tiles)?))
+    }
+
+    fn part_2(tiles: &Self::Input<'_>) -> Result<usize, SolveError> {
+        Ok(solve_2(&assemble(tiles)?.get_picture()))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test() {
-        let input = "Tile 0000:
+    const MONSTERS: &str = "Tile 0000:
 .####...#####..#...###..
 #####..#..#.#.####..#.#.
 .#.#...#.###...#.##.##..
@@ -368,7 +709,261 @@ mod tests {
 #....##..#.#########..##
 #...#.....#..##...###.##
 #..###....##.#...##.##.#";
-        let tile = Tile::new(input);
-        assert_eq!(solve_2(tile), 273);
+
+    #[test]
+    fn test() {
+        let tile = Tile::new(MONSTERS).unwrap();
+        assert_eq!(solve_2(&tile), 273);
+
+        let search = tile.search(&Pattern::sea_monster());
+        assert_eq!(
+            search.matches,
+            [
+                Match {
+                    row: 2,
+                    column: 2,
+                    orientation: Orientation::IDENTITY
+                },
+                Match {
+                    row: 16,
+                    column: 1,
+                    orientation: Orientation::IDENTITY
+                }
+            ]
+        );
+    }
+
+    /// Cuts a random picture of `width` by `width` tiles sharing their borders, in random
+    /// orientations, the tile on row `r` and column `c` has the id `10 * r + c + 1`.
+    fn cut_tiles(width: usize, seed: u64) -> Vec<Tile> {
+        cut_oriented_tiles(width, seed)
+            .into_iter()
+            .map(|(tile, _)| tile)
+            .collect()
+    }
+
+    /// The tiles of `cut_tiles` along with the orientation they were given.
+    fn cut_oriented_tiles(width: usize, seed: u64) -> Vec<(Tile, Orientation)> {
+        let mut state = seed;
+        let mut random = move || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            state >> 33
+        };
+        let size = 10;
+        let side = width * (size - 1) + 1;
+        let pixels = (0..side)
+            .map(|_| {
+                (0..side)
+                    .map(|_| if random() % 2 == 0 { '#' } else { '.' })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut tiles = vec![];
+        for row in 0..width {
+            for column in 0..width {
+                let tile = Tile {
+                    id: (10 * row + column + 1) as u16,
+                    pixels: pixels[row * (size - 1)..][..size]
+                        .iter()
+                        .map(|line| line[column * (size - 1)..][..size].to_vec())
+                        .collect(),
+                };
+                let orientation = Orientation::ALL[random() as usize % 8];
+                tiles.push((tile.oriented(orientation), orientation));
+            }
+        }
+        let shift = random() as usize % tiles.len();
+        tiles.rotate_left(shift);
+        tiles
+    }
+
+    #[test]
+    fn test_assemble() {
+        for seed in 0..5 {
+            let picture = Picture::new(cut_tiles(4, seed)).unwrap();
+            assert_eq!(solve_1(&picture), 4 * 31 * 34);
+            assert_eq!(picture.get_picture().size(), 32);
+            // the neighbours of the first tile are the ones next to it in the original picture
+            let first = picture.tile(0, 0).id();
+            let right = picture.tile(0, 1).id();
+            let below = picture.tile(1, 0).id();
+            let mut neighbours = [right.min(below), right.max(below)];
+            neighbours.sort_unstable();
+            let expected = match first {
+                1 => [2, 11],
+                4 => [3, 14],
+                31 => [21, 32],
+                _ => [24, 33],
+            };
+            assert_eq!(neighbours, expected);
+
+            // the picture is the original one in some orientation, the same for every tile
+            let tiles = cut_oriented_tiles(4, seed);
+            let given = tiles
+                .iter()
+                .map(|&(ref tile, orientation)| (tile.id(), orientation))
+                .collect::<HashMap<_, _>>();
+            let picture = Picture::new(tiles.into_iter().map(|(tile, _)| tile).collect()).unwrap();
+            let mut orientations = (0..16)
+                .map(|index| {
+                    let tile = picture.tile(index / 4, index % 4);
+                    given[&tile.id()].then(tile.orientation())
+                })
+                .collect::<Vec<_>>();
+            orientations.dedup();
+            assert_eq!(orientations.len(), 1);
+        }
+        assert_eq!(
+            Picture::new(cut_tiles(1, 0)).map(|picture| picture.width()),
+            Ok(1)
+        );
+    }
+
+    #[test]
+    fn test_assembly_errors() {
+        let mut tiles = cut_tiles(2, 7);
+        tiles.pop();
+        assert_eq!(Picture::new(tiles).err(), Some(AssemblyError::NotSquare(3)));
+
+        // the tiles of two different pictures
+        let mut tiles = cut_tiles(2, 1);
+        tiles.truncate(2);
+        tiles.extend(cut_tiles(2, 2).into_iter().take(2));
+        assert_eq!(Picture::new(tiles).err(), Some(AssemblyError::NoAssembly));
+
+        // empty tiles fit everywhere
+        let empty = Tile::new("Tile 1:\n...\n...\n...").unwrap();
+        let tiles = (1..=4)
+            .map(|id| Tile {
+                id,
+                ..empty.clone()
+            })
+            .collect();
+        assert_eq!(
+            Picture::new(tiles).err(),
+            Some(AssemblyError::MultipleAssemblies)
+        );
+
+        let tiles = vec![
+            empty.clone(),
+            empty.clone(),
+            empty.clone(),
+            Tile::new("Tile 5:\n..\n..").unwrap(),
+        ];
+        assert_eq!(
+            Picture::new(tiles).err(),
+            Some(AssemblyError::SizeMismatch(5))
+        );
+    }
+
+    #[test]
+    fn test_orientation() {
+        let cells = (0..3).flat_map(|row| (0..5).map(move |column| (row, column)));
+        for a in Orientation::ALL {
+            assert_eq!(a.then(a.inverse()), Orientation::IDENTITY);
+            assert_eq!(a.inverse().then(a), Orientation::IDENTITY);
+            let dimensions = if a.rotation() % 2 == 0 {
+                (3, 5)
+            } else {
+                (5, 3)
+            };
+            for b in Orientation::ALL {
+                for cell in cells.clone() {
+                    assert_eq!(
+                        b.apply(a.apply(cell, (3, 5)), dimensions),
+                        a.then(b).apply(cell, (3, 5)),
+                        "{} then {}",
+                        a,
+                        b
+                    );
+                }
+            }
+        }
+        assert_eq!(
+            Orientation::new(false, 1).then(Orientation::new(true, 0)),
+            Orientation::new(true, 3)
+        );
+        assert_eq!(
+            Orientation::new(true, 2).to_string(),
+            "flipped and rotated 180"
+        );
+
+        let tile = Tile::new("Tile 1:\n#..\n...\n..#").unwrap();
+        let view = tile.view(Orientation::new(false, 1));
+        assert_eq!(
+            (view.get(0, 2), view.get(2, 0), view.get(0, 0)),
+            ('#', '#', '.')
+        );
+        let view = tile.view(Orientation::new(true, 0));
+        assert_eq!((view.get(0, 2), view.get(2, 0)), ('#', '#'));
+        assert_eq!(view.to_tile().to_string(), "id: 1\n..#\n...\n#..\n");
+    }
+
+    #[test]
+    fn test_pattern() {
+        let image = Tile::new("Tile 1:\n###.\n#...\n....\n...#").unwrap();
+        let pattern = Pattern::new("\n .##  \n").unwrap();
+        assert_eq!((pattern.height(), pattern.width()), (1, 2));
+        // a line has only 2 orientations, the matches overlap
+        let search = image.search(&pattern);
+        let found = search
+            .matches
+            .iter()
+            .map(|m| (m.row, m.column, m.orientation.index()))
+            .collect::<Vec<_>>();
+        assert_eq!(found, [(0, 0, 0), (0, 1, 0), (0, 0, 1)]);
+        assert_eq!(search.roughness, 1);
+
+        let corner = Pattern::new("##\n#.").unwrap();
+        assert_eq!(corner.orientations().len(), 4);
+        assert_eq!(image.search(&corner).matches.len(), 1);
+        assert_eq!(
+            Pattern::new("..\n").err().unwrap(),
+            ParseError::new(2, 1, "", "expected at least one `#`")
+        );
+    }
+
+    #[test]
+    fn test_images() {
+        let tile = Tile::new("Tile 1:\n#.\n.#").unwrap();
+        let image = tile.to_image();
+        assert_eq!((image.get(0, 0), image.get(1, 0)), (WAVE, WATER));
+
+        let picture = Picture::new(cut_tiles(2, 3)).unwrap();
+        let image = picture.to_image(&RenderOptions::default());
+        assert_eq!(image.width(), 20);
+        let image = picture.to_image(&RenderOptions {
+            scale: 2,
+            seams: true,
+            ids: true,
+        });
+        assert_eq!((image.width(), image.height()), (41, 41));
+        assert_eq!(image.get(20, 7), SEAM);
+        assert!((0..20).any(|x| image.get(x, 1) == TILE_ID));
+
+        let monsters = Tile::new(MONSTERS)
+            .unwrap()
+            .highlight(&Pattern::sea_monster());
+        let highlighted = (0..24)
+            .flat_map(|y| (0..24).map(move |x| (x, y)))
+            .filter(|&(x, y)| monsters.get(x, y) == HIGHLIGHT)
+            .count();
+        assert_eq!(highlighted, 30);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let input = "Tile 1:\n#.\n.#\n\nTile 2:\n#.\n.o";
+        assert_eq!(
+            parse_input(input).err().unwrap(),
+            ParseError::new(7, 2, "o", "expected one of `.#`")
+        );
+        assert_eq!(
+            Tile::new("Tile 1:\n").err().unwrap(),
+            ParseError::new(1, 1, "Tile 1:", "expected a square tile")
+        );
+        assert!(parse_input("Tile 1:\n#.\n.#\n\nTile 2:").is_err());
     }
 }
@@ -1,61 +1,62 @@
+use crate::{
+    error::{ParseError, SolveError},
+    solution::Solution,
+};
 use std::collections::{HashMap, HashSet};
 
-struct IngredientList<'a> {
+#[derive(Clone)]
+pub struct IngredientList<'a> {
     ingredients_count: HashMap<&'a str, u32>,
     possible_allergens: HashMap<&'a str, HashSet<&'a str>>,
 }
 
 impl<'a> IngredientList<'a> {
-    fn new(input: &'a str) -> Self {
-        let (ingredients_count, possible_allergens) = input.lines().fold(
-            (
-                HashMap::<&str, u32>::new(),
-                HashMap::<&str, HashSet<&str>>::new(),
-            ),
-            |(mut ingredients_count, mut possible_allergens), line| {
-                let mut line_parts = line.split(" (contains ");
-                let ingredients = line_parts
-                    .next()
-                    .unwrap()
-                    .split(' ')
-                    .collect::<HashSet<_>>();
-                ingredients.iter().for_each(|&ingredient| {
-                    if let Some(count) = ingredients_count.get_mut(ingredient) {
-                        *count += 1;
-                    } else {
-                        ingredients_count.insert(ingredient, 1);
-                    }
-                });
-                let allergens = line_parts
-                    .next()
-                    .unwrap()
-                    .trim_end_matches(')')
-                    .split(", ")
-                    .collect::<Vec<_>>();
-                for allergen in allergens {
-                    if let Some(possible_ingredients) = possible_allergens.get_mut(allergen) {
-                        possible_ingredients.retain(|ingredient| ingredients.contains(ingredient))
-                    } else {
-                        possible_allergens.insert(allergen, ingredients.clone());
-                    }
+    pub fn new(input: &'a str) -> Result<Self, ParseError> {
+        let mut ingredients_count = HashMap::<&str, u32>::new();
+        let mut possible_allergens = HashMap::<&str, HashSet<&str>>::new();
+        for line in input.lines() {
+            let mut line_parts = line.splitn(2, " (contains ");
+            let ingredients = line_parts.next().unwrap();
+            if ingredients.is_empty() {
+                return Err(ParseError::at(input, ingredients, "expected ingredients"));
+            }
+            let ingredients = ingredients.split(' ').collect::<HashSet<_>>();
+            ingredients.iter().for_each(|&ingredient| {
+                if let Some(count) = ingredients_count.get_mut(ingredient) {
+                    *count += 1;
+                } else {
+                    ingredients_count.insert(ingredient, 1);
                 }
-                (ingredients_count, possible_allergens)
-            },
-        );
-        Self {
+            });
+            // allergens are not always listed
+            let Some(allergens) = line_parts.next() else {
+                continue;
+            };
+            let allergens = allergens.strip_suffix(')').ok_or_else(|| {
+                ParseError::at(input, &allergens[allergens.len()..], "expected `)`")
+            })?;
+            for allergen in allergens.split(", ") {
+                if let Some(possible_ingredients) = possible_allergens.get_mut(allergen) {
+                    possible_ingredients.retain(|ingredient| ingredients.contains(ingredient))
+                } else {
+                    possible_allergens.insert(allergen, ingredients.clone());
+                }
+            }
+        }
+        Ok(Self {
             ingredients_count,
             possible_allergens,
-        }
+        })
     }
 
-    fn reduce_possible_allergens(&mut self) {
+    pub fn reduce_possible_allergens(&mut self) {
         let mut found_allergens: Vec<(&str, &str)> = self
             .possible_allergens
             .iter()
             .filter(|(_, ingredients)| ingredients.len() == 1)
             .map(|(allergen, ingredient)| (*allergen, *ingredient.iter().next().unwrap()))
             .collect::<Vec<_>>();
-        while found_allergens.len() > 0 {
+        while !found_allergens.is_empty() {
             let mut next = vec![];
             for (found_allergen, ingredient) in found_allergens {
                 for (&allergen, ingredients) in self.possible_allergens.iter_mut() {
@@ -72,7 +73,7 @@ impl<'a> IngredientList<'a> {
     }
 }
 
-fn solve_1(ingredient_list: &IngredientList) -> u32 {
+pub fn solve_1(ingredient_list: &IngredientList) -> u32 {
     let all_possible_allergens = ingredient_list
         .possible_allergens
         .values()
@@ -90,34 +91,56 @@ fn solve_1(ingredient_list: &IngredientList) -> u32 {
         })
 }
 
-fn solve_2(list: &mut IngredientList) -> String {
+/// The ingredients sorted by allergen, every allergen must be found in a single ingredient.
+pub fn solve_2(list: &mut IngredientList) -> Result<String, SolveError> {
     list.reduce_possible_allergens();
     let mut vec = list
         .possible_allergens
         .iter()
-        .map(|(allergen, ingredients)| (*allergen, *ingredients.iter().next().unwrap()))
-        .collect::<Vec<_>>();
-    vec.sort_by(|(allergen, _), (allergen2, _)| allergen.cmp(allergen2));
-    vec.iter()
+        .map(|(allergen, ingredients)| {
+            let mut ingredients = ingredients.iter();
+            match (ingredients.next(), ingredients.next()) {
+                (Some(ingredient), None) => Ok((*allergen, *ingredient)),
+                (None, _) => Err(SolveError::NoSolution(format!(
+                    "no ingredient can contain {}",
+                    allergen
+                ))),
+                (Some(_), Some(_)) => Err(SolveError::NoSolution(format!(
+                    "several ingredients can contain {}",
+                    allergen
+                ))),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    vec.sort_by_key(|(allergen, _)| *allergen);
+    Ok(vec
+        .iter()
         .map(|(_, ingredient)| *ingredient)
         .collect::<Vec<_>>()
-        .join(",")
+        .join(","))
 }
 
-pub fn run() {
-    let timer = std::time::Instant::now();
-    let input = std::fs::read_to_string("inputs/day21").unwrap();
-    let mut list = IngredientList::new(&input);
-    println!(
-        "day 21 solution 1 : {}, {}us",
-        solve_1(&list),
-        timer.elapsed().as_micros()
-    );
-    println!(
-        "day 21 solution 2 : {}, {}us",
-        solve_2(&mut list),
-        timer.elapsed().as_micros()
-    );
+pub struct Day21;
+
+impl Solution for Day21 {
+    type Input<'a> = IngredientList<'a>;
+    type Output1 = u32;
+    type Output2 = Result<String, SolveError>;
+
+    const DAY: u8 = 21;
+    const TITLE: &'static str = "Allergen Assessment";
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        IngredientList::new(input)
+    }
+
+    fn part_1(list: &Self::Input<'_>) -> u32 {
+        solve_1(list)
+    }
+
+    fn part_2(list: &Self::Input<'_>) -> Result<String, SolveError> {
+        solve_2(&mut list.clone())
+    }
 }
 
 #[cfg(test)]
@@ -130,7 +153,7 @@ mod tests {
 trh fvjkl sbzzf mxmxvkd (contains dairy)
 sqjhc fvjkl (contains soy)
 sqjhc mxmxvkd sbzzf (contains fish)";
-        let list = IngredientList::new(&input);
+        let list = IngredientList::new(input).unwrap();
         assert_eq!(solve_1(&list), 5);
     }
 
@@ -140,7 +163,20 @@ sqjhc mxmxvkd sbzzf (contains fish)";
 trh fvjkl sbzzf mxmxvkd (contains dairy)
 sqjhc fvjkl (contains soy)
 sqjhc mxmxvkd sbzzf (contains fish)";
-        let mut list = IngredientList::new(&input);
-        assert_eq!(solve_2(&mut list), "mxmxvkd,sqjhc,fvjkl");
+        let mut list = IngredientList::new(input).unwrap();
+        assert_eq!(solve_2(&mut list), Ok("mxmxvkd,sqjhc,fvjkl".to_owned()));
+    }
+
+    #[test]
+    fn test_no_solution() {
+        let mut list = IngredientList::new("a b (contains dairy)\nc (contains dairy)").unwrap();
+        assert_eq!(
+            solve_2(&mut list),
+            Err(SolveError::NoSolution(
+                "no ingredient can contain dairy".to_owned()
+            ))
+        );
+        let mut list = IngredientList::new("a b (contains dairy)").unwrap();
+        assert!(matches!(solve_2(&mut list), Err(SolveError::NoSolution(_))));
     }
 }
@@ -1,40 +1,39 @@
+use crate::{
+    cancel::checkpoint,
+    error::{next_token, parse_token, ParseError, SolveError},
+    solution::Solution,
+};
 use std::collections::HashSet;
 
-enum Player {
+pub enum Player {
     One,
     Two,
 }
 
-struct Game {
+#[derive(Clone)]
+pub struct Game {
     player_one: Vec<u8>,
     player_two: Vec<u8>,
 }
 
 impl Game {
-    fn new(input: &str) -> Self {
+    pub fn new(input: &str) -> Result<Self, ParseError> {
         let mut players = input.split("\n\n");
-        let player_one = players
-            .next()
-            .unwrap()
-            .lines()
-            .skip(1)
-            .map(|s| s.parse::<u8>().unwrap())
-            .collect();
-        let player_two = players
-            .next()
-            .unwrap()
-            .lines()
-            .skip(1)
-            .map(|s| s.parse::<u8>().unwrap())
-            .collect();
-        Self {
+        let player_one = parse_deck(input, players.next().unwrap(), "Player 1:")?;
+        let player_two = parse_deck(
+            input,
+            next_token(&mut players, input, "expected the deck of player 2")?,
+            "Player 2:",
+        )?;
+        Ok(Self {
             player_one,
             player_two,
-        }
+        })
     }
 
-    fn run(&mut self) {
-        while self.player_one.len() > 0 && self.player_two.len() > 0 {
+    pub fn run(&mut self) -> Result<(), SolveError> {
+        while !self.player_one.is_empty() && !self.player_two.is_empty() {
+            checkpoint()?;
             let one = self.player_one.remove(0);
             let two = self.player_two.remove(0);
             if one > two {
@@ -45,14 +44,16 @@ impl Game {
                 self.player_two.push(one);
             }
         }
+        Ok(())
     }
 
-    fn run_recursive(&mut self) -> Player {
+    pub fn run_recursive(&mut self) -> Result<Player, SolveError> {
         let mut previous_configurations = HashSet::new();
         while !previous_configurations.contains(&self.player_one)
-            && self.player_one.len() > 0
-            && self.player_two.len() > 0
+            && !self.player_one.is_empty()
+            && !self.player_two.is_empty()
         {
+            checkpoint()?;
             previous_configurations.insert(self.player_one.clone());
 
             let one = self.player_one.remove(0);
@@ -60,10 +61,10 @@ impl Game {
             let winner =
                 if one as usize <= self.player_one.len() && two as usize <= self.player_two.len() {
                     let mut game = Game {
-                        player_one: self.player_one[..one as usize].iter().copied().collect(),
-                        player_two: self.player_two[..two as usize].iter().copied().collect(),
+                        player_one: self.player_one[..one as usize].to_vec(),
+                        player_two: self.player_two[..two as usize].to_vec(),
                     };
-                    game.run_recursive()
+                    game.run_recursive()?
                 } else if one > two {
                     Player::One
                 } else {
@@ -81,54 +82,74 @@ impl Game {
             }
         }
 
-        if self.player_one.len() == 0 {
+        Ok(if self.player_one.is_empty() {
             Player::Two
         } else {
             Player::One
-        }
+        })
     }
 }
 
-fn solve_1(game: &mut Game) -> u32 {
-    game.run();
-    let winner = if game.player_one.len() > 0 {
+pub fn solve_1(game: &mut Game) -> Result<u32, SolveError> {
+    game.run()?;
+    let winner = if !game.player_one.is_empty() {
         &game.player_one
     } else {
         &game.player_two
     };
-    winner.iter().enumerate().fold(0, |acc, (index, &value)| {
-        acc + value as u32 * (winner.len() - index) as u32
-    })
+    Ok(score(winner))
 }
 
-fn solve_2(game: &mut Game) -> u32 {
-    let winner = match game.run_recursive() {
+pub fn solve_2(game: &mut Game) -> Result<u32, SolveError> {
+    let winner = match game.run_recursive()? {
         Player::One => &game.player_one,
         Player::Two => &game.player_two,
     };
-    winner.iter().enumerate().fold(0, |acc, (index, &value)| {
-        acc + value as u32 * (winner.len() - index) as u32
+    Ok(score(winner))
+}
+
+fn score(deck: &[u8]) -> u32 {
+    deck.iter().enumerate().fold(0, |acc, (index, &value)| {
+        acc + value as u32 * (deck.len() - index) as u32
     })
 }
 
-pub fn run() {
-    let timer = std::time::Instant::now();
-    let input = std::fs::read_to_string("inputs/day22").unwrap();
-    let mut game = Game::new(&input);
-    let mut game2 = Game {
-        player_one: game.player_one.clone(),
-        player_two: game.player_two.clone(),
-    };
-    println!(
-        "day 22 solution 1 : {}, {}us",
-        solve_1(&mut game),
-        timer.elapsed().as_micros()
-    );
-    println!(
-        "day 22 solution 2 : {}, {}us",
-        solve_2(&mut game2),
-        timer.elapsed().as_micros()
-    );
+fn parse_deck(input: &str, deck: &str, header: &str) -> Result<Vec<u8>, ParseError> {
+    let mut lines = deck.lines();
+    let first = next_token(&mut lines, input, "expected a deck")?;
+    if first != header {
+        return Err(ParseError::at(
+            input,
+            first,
+            &format!("expected `{}`", header),
+        ));
+    }
+    lines
+        .map(|card| parse_token(input, card, "expected a card number"))
+        .collect()
+}
+
+pub struct Day22;
+
+impl Solution for Day22 {
+    type Input<'a> = Game;
+    type Output1 = Result<u32, SolveError>;
+    type Output2 = Result<u32, SolveError>;
+
+    const DAY: u8 = 22;
+    const TITLE: &'static str = "Crab Combat";
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        Game::new(input)
+    }
+
+    fn part_1(game: &Self::Input<'_>) -> Result<u32, SolveError> {
+        solve_1(&mut game.clone())
+    }
+
+    fn part_2(game: &Self::Input<'_>) -> Result<u32, SolveError> {
+        solve_2(&mut game.clone())
+    }
 }
 
 #[cfg(test)]
@@ -137,14 +158,23 @@ mod tests {
     #[test]
     fn test_solution_1() {
         let input = "Player 1:\n9\n2\n6\n3\n1\n\nPlayer 2:\n5\n8\n4\n7\n10";
-        let mut game = Game::new(&input);
-        assert_eq!(solve_1(&mut game), 306);
+        let mut game = Game::new(input).unwrap();
+        assert_eq!(solve_1(&mut game), Ok(306));
     }
 
     #[test]
     fn test_solution_2() {
         let input = "Player 1:\n9\n2\n6\n3\n1\n\nPlayer 2:\n5\n8\n4\n7\n10";
-        let mut game = Game::new(&input);
-        assert_eq!(solve_2(&mut game), 291);
+        let mut game = Game::new(input).unwrap();
+        assert_eq!(solve_2(&mut game), Ok(291));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let input = "Player 1:\n9\n2\n\nPlayer 2:\n5\n-8";
+        assert_eq!(
+            Game::new(input).err().unwrap(),
+            ParseError::new(7, 1, "-8", "expected a card number")
+        );
     }
 }
@@ -1,92 +1,298 @@
-fn solve_1(input: &str) -> String {
-    let numbers = input
-        .chars()
-        .map(|c| c.to_digit(10).unwrap())
-        .collect::<Vec<_>>();
-    let map = solve(&numbers, 100);
-    let mut result = String::with_capacity(8);
-    let mut current_number = 1;
-    while result.len() < 8 {
-        current_number = map[current_number as usize - 1];
-        result.push_str(&current_number.to_string());
+use crate::{
+    cancel::checkpoint,
+    error::{ParseError, SolveError},
+    solution::Solution,
+};
+use std::fmt::Display;
+
+/// Parses the cup labels, which must be the numbers from 1 to their count in any order.
+pub fn parse_input(input: &str) -> Result<Vec<u32>, ParseError> {
+    let count = input.chars().count() as u32;
+    let mut labels = Vec::with_capacity(count as usize);
+    for (index, c) in input.char_indices() {
+        match c.to_digit(10) {
+            Some(label) if label >= 1 && label <= count && !labels.contains(&label) => {
+                labels.push(label)
+            }
+            _ => {
+                return Err(ParseError::at(
+                    input,
+                    &input[index..index + c.len_utf8()],
+                    &format!("expected a distinct cup label between 1 and {}", count),
+                ))
+            }
+        }
+    }
+    if labels.is_empty() {
+        return Err(ParseError::at(input, input, "expected cup labels"));
     }
-    result
+    Ok(labels)
 }
 
-fn solve_2(input: &str) -> u128 {
-    let mut numbers = input
-        .chars()
-        .map(|c| c.to_digit(10).unwrap())
-        .collect::<Vec<_>>();
-    numbers.extend(10..=1000000);
-    let map = solve(&numbers, 10000000);
-    let one_next = map[0];
-    let one_next_next = map[one_next as usize - 1];
-    one_next as u128 * one_next_next as u128
+/// Rules of a game of crab cups : the labels read from the input are followed by the next labels
+/// up to `cups`, then `pick_up` cups are moved at each of the `moves` moves.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Game {
+    pub cups: usize,
+    pub moves: usize,
+    pub pick_up: usize,
 }
 
-fn solve(numbers: &Vec<u32>, iterations: usize) -> Vec<u32> {
-    let mut map = vec![0; numbers.len()];
-    for i in 0..numbers.len() {
-        map[numbers[i] as usize - 1] = numbers[(i + 1) % numbers.len()];
+/// Why a game cannot be played.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameError {
+    /// The number of cups is less than the number of labels.
+    TooManyLabels(usize),
+    /// No cup is left for a destination once the cups are picked up.
+    NoDestination,
+    /// The game stopped at a checkpoint, see `cancel::checkpoint`.
+    Cancelled,
+}
+
+impl Display for GameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameError::TooManyLabels(count) => {
+                write!(f, "{} labels do not fit in the cups", count)
+            }
+            GameError::NoDestination => {
+                write!(f, "not enough cups left for a destination")
+            }
+            GameError::Cancelled => write!(f, "cancelled"),
+        }
     }
+}
 
-    let mut current_number = numbers[0];
-    for _ in 0..iterations {
-        let mut destination =
-            (current_number + numbers.len() as u32 - 2) % numbers.len() as u32 + 1;
-
-        let first_following = map[current_number as usize - 1];
-        let second_following = map[first_following as usize - 1];
-        let third_following = map[second_following as usize - 1];
-        while destination == first_following
-            || destination == second_following
-            || destination == third_following
-        {
-            destination = (destination + numbers.len() as u32 - 2) % numbers.len() as u32 + 1;
+impl Game {
+    pub fn play(&self, labels: &[u32]) -> Result<Circle, GameError> {
+        let cups = self.cups.max(1);
+        if cups < labels.len() {
+            return Err(GameError::TooManyLabels(labels.len()));
+        }
+        if self.pick_up + 2 > cups {
+            return Err(GameError::NoDestination);
         }
 
-        // update current next to be next of the third
-        map[current_number as usize - 1] = map[third_following as usize - 1];
-        // update third next to be next of destination
-        map[third_following as usize - 1] = map[destination as usize - 1];
-        // update destination next to be next of current
-        map[destination as usize - 1] = first_following;
+        // next[label - 1] is the label of the cup clockwise of it
+        let mut next = vec![0; cups];
+        let mut numbers = labels
+            .iter()
+            .copied()
+            .chain(labels.len() as u32 + 1..=cups as u32);
+        let first = numbers.next().unwrap_or(1);
+        let last = numbers.fold(first, |previous, label| {
+            next[previous as usize - 1] = label;
+            label
+        });
+        next[last as usize - 1] = first;
+
+        let previous_label = |label: u32| (label + cups as u32 - 2) % cups as u32 + 1;
+        let mut picked = Vec::with_capacity(self.pick_up);
+        let mut current = first;
+        for index in 0..self.moves {
+            if index % 65536 == 0 {
+                checkpoint().map_err(|_| GameError::Cancelled)?;
+            }
+            picked.clear();
+            let mut last_picked = current;
+            for _ in 0..self.pick_up {
+                last_picked = next[last_picked as usize - 1];
+                picked.push(last_picked);
+            }
 
-        current_number = map[current_number as usize - 1];
+            let mut destination = previous_label(current);
+            while picked.contains(&destination) {
+                destination = previous_label(destination);
+            }
+
+            if let Some(&first_picked) = picked.first() {
+                // the cups following the picked ones now follow the current cup
+                next[current as usize - 1] = next[last_picked as usize - 1];
+                // the picked cups are inserted right after the destination
+                next[last_picked as usize - 1] = next[destination as usize - 1];
+                next[destination as usize - 1] = first_picked;
+            }
+
+            current = next[current as usize - 1];
+        }
+
+        Ok(Circle { next, current })
+    }
+}
+
+/// Cups after a game, in a circle.
+#[derive(Clone, Debug)]
+pub struct Circle {
+    next: Vec<u32>,
+    current: u32,
+}
+
+impl Circle {
+    /// Every cup clockwise, starting with the current one.
+    pub fn order(&self) -> impl Iterator<Item = u32> + '_ {
+        self.from(self.current)
     }
 
-    map
+    /// The other cups clockwise after `label`.
+    pub fn after(&self, label: u32) -> impl Iterator<Item = u32> + '_ {
+        self.from(label).skip(1)
+    }
+
+    fn from(&self, label: u32) -> impl Iterator<Item = u32> + '_ {
+        std::iter::successors(Some(label), move |&label| {
+            Some(self.next[label as usize - 1])
+        })
+        .take(self.next.len())
+    }
+}
+
+pub fn solve_1(labels: &[u32]) -> Result<String, SolveError> {
+    let game = Game {
+        cups: labels.len(),
+        moves: 100,
+        pick_up: 3,
+    };
+    Ok(play(&game, labels)?
+        .after(1)
+        .map(|label| label.to_string())
+        .collect())
 }
 
-pub fn run() {
-    let timer = std::time::Instant::now();
-    let input = "253149867";
-    println!(
-        "day 23 solution 1 : {}, {}us",
-        solve_1(&input),
-        timer.elapsed().as_micros()
-    );
-    println!(
-        "day 23 solution 2 : {}, {}us",
-        solve_2(&input),
-        timer.elapsed().as_micros()
-    );
+pub fn solve_2(labels: &[u32]) -> Result<u128, SolveError> {
+    let game = Game {
+        cups: 1000000,
+        moves: 10000000,
+        pick_up: 3,
+    };
+    Ok(play(&game, labels)?
+        .after(1)
+        .take(2)
+        .map(|label| label as u128)
+        .product())
+}
+
+fn play(game: &Game, labels: &[u32]) -> Result<Circle, SolveError> {
+    game.play(labels).map_err(|error| match error {
+        GameError::Cancelled => SolveError::Cancelled,
+        _ => SolveError::NoSolution(error.to_string()),
+    })
+}
+
+pub struct Day23;
+
+impl Solution for Day23 {
+    type Input<'a> = Vec<u32>;
+    type Output1 = Result<String, SolveError>;
+    type Output2 = Result<u128, SolveError>;
+
+    const DAY: u8 = 23;
+    const TITLE: &'static str = "Crab Cups";
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(labels: &Self::Input<'_>) -> Result<String, SolveError> {
+        solve_1(labels)
+    }
+
+    fn part_2(labels: &Self::Input<'_>) -> Result<u128, SolveError> {
+        solve_2(labels)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cancel::CancelFlag;
 
     #[test]
     fn test_solution_1() {
         let input = "389125467";
-        assert_eq!(solve_1(&input), "67384529");
+        assert_eq!(
+            solve_1(&parse_input(input).unwrap()),
+            Ok("67384529".to_owned())
+        );
     }
 
     #[test]
     fn test_solution_2() {
         let input = "389125467";
-        assert_eq!(solve_2(&input), 149245887792);
+        assert_eq!(solve_2(&parse_input(input).unwrap()), Ok(149245887792));
+    }
+
+    #[test]
+    fn test_game() {
+        let labels = parse_input("389125467").unwrap();
+        let game = Game {
+            cups: 9,
+            moves: 10,
+            pick_up: 3,
+        };
+        assert_eq!(
+            game.play(&labels).unwrap().order().collect::<Vec<_>>(),
+            vec![8, 3, 7, 4, 1, 9, 2, 6, 5]
+        );
+        assert_eq!(
+            game.play(&labels).unwrap().after(1).collect::<Vec<_>>(),
+            vec![9, 2, 6, 5, 8, 3, 7, 4]
+        );
+
+        // extra cups and another pick up size
+        let game = Game {
+            cups: 12,
+            moves: 1,
+            pick_up: 2,
+        };
+        assert_eq!(
+            game.play(&labels).unwrap().order().collect::<Vec<_>>(),
+            vec![1, 2, 8, 9, 5, 4, 6, 7, 10, 11, 12, 3]
+        );
+        let game = Game {
+            cups: 9,
+            moves: 5,
+            pick_up: 0,
+        };
+        assert_eq!(
+            game.play(&labels).unwrap().order().collect::<Vec<_>>(),
+            vec![5, 4, 6, 7, 3, 8, 9, 1, 2]
+        );
+
+        let game = Game {
+            cups: 5,
+            moves: 1,
+            pick_up: 4,
+        };
+        assert_eq!(game.play(&[2, 1]).err(), Some(GameError::NoDestination));
+        assert_eq!(game.play(&labels).err(), Some(GameError::TooManyLabels(9)));
+        assert!(matches!(
+            solve_1(&parse_input("21").unwrap()),
+            Err(SolveError::NoSolution(_))
+        ));
+    }
+
+    #[test]
+    fn test_cancel() {
+        let labels = parse_input("389125467").unwrap();
+        let result = std::thread::spawn(move || {
+            let flag = CancelFlag::new();
+            flag.install();
+            flag.cancel();
+            solve_2(&labels)
+        });
+        assert_eq!(result.join().unwrap(), Err(SolveError::Cancelled));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert_eq!(
+            parse_input("389125463"),
+            Err(ParseError::new(
+                1,
+                9,
+                "3",
+                "expected a distinct cup label between 1 and 9"
+            ))
+        );
     }
 }
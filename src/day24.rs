@@ -1,6 +1,7 @@
+use crate::{error::ParseError, solution::Solution};
 use std::collections::{HashMap, HashSet};
 
-fn get_adjacent_tiles(tile: &(i8, i8)) -> [(i8, i8); 6] {
+pub fn get_adjacent_tiles(tile: &(i8, i8)) -> [(i8, i8); 6] {
     [
         // e, se, sw, w, nw, and ne
         (tile.0, tile.1 + 1),
@@ -31,10 +32,13 @@ fn get_adjacent_tiles(tile: &(i8, i8)) -> [(i8, i8); 6] {
 3   x x x
 4  x x x
 */
-fn get_destination_from_reference(reference: (i8, i8), directions: &str) -> (i8, i8) {
+pub fn get_destination_from_reference(
+    reference: (i8, i8),
+    directions: &str,
+) -> Result<(i8, i8), ParseError> {
     let mut rest = directions;
     let mut destination = reference;
-    while rest.len() > 0 {
+    while !rest.is_empty() {
         // e, se, sw, w, nw, and ne
         match rest {
             _ if rest.starts_with("e") => {
@@ -89,39 +93,46 @@ fn get_destination_from_reference(reference: (i8, i8), directions: &str) -> (i8,
                     },
                 );
             }
-            _ => panic!("invalid direction"),
+            _ => {
+                let end = rest.chars().next().map_or(0, char::len_utf8);
+                return Err(ParseError::at(
+                    directions,
+                    &rest[..end],
+                    "invalid direction, expected e, se, sw, w, nw or ne",
+                ));
+            }
         }
     }
-    destination
+    Ok(destination)
 }
 
-struct Game {
+#[derive(Clone)]
+pub struct Game {
     black_tiles: HashSet<(i8, i8)>,
 }
 
 impl Game {
-    fn new(input: &str) -> Self {
-        let black_tiles = input
-            .lines()
-            .fold(HashSet::<(i8, i8)>::new(), |mut set, directions| {
-                let destination = get_destination_from_reference((0, 0), directions);
-                if set.contains(&destination) {
-                    set.remove(&destination);
-                } else {
-                    set.insert(destination);
-                }
-                set
-            });
-        Self { black_tiles }
+    pub fn new(input: &str) -> Result<Self, ParseError> {
+        let mut black_tiles = HashSet::<(i8, i8)>::new();
+        for directions in input.lines() {
+            let destination = get_destination_from_reference((0, 0), directions)
+                .map_err(|error| error.within(input, directions))?;
+            if black_tiles.contains(&destination) {
+                black_tiles.remove(&destination);
+            } else {
+                black_tiles.insert(destination);
+            }
+        }
+        Ok(Self { black_tiles })
     }
 
-    fn count_black_tiles(&self) -> usize {
+    pub fn count_black_tiles(&self) -> usize {
         self.black_tiles.len()
     }
 
     /// - Any black tile with zero or more than 2 black tiles immediately adjacent to it is flipped to white.
     /// - Any white tile with exactly 2 black tiles immediately adjacent to it is flipped to black.
-    fn run_n_days(&mut self, days: u32) {
+    pub fn run_n_days(&mut self, days: u32) {
         for _ in 0..days {
             let mut adjacent_count = HashMap::new();
             for black_tile in self.black_tiles.iter() {
@@ -145,21 +156,29 @@ impl Game {
     }
 }
 
-pub fn run() {
-    let timer = std::time::Instant::now();
-    let input = std::fs::read_to_string("inputs/day24").unwrap();
-    let mut game = Game::new(&input);
-    println!(
-        "day 24 solution 1 : {}, {}us",
-        game.count_black_tiles(),
-        timer.elapsed().as_micros()
-    );
-    game.run_n_days(100);
-    println!(
-        "day 24 solution 2 : {}, {}us",
-        game.count_black_tiles(),
-        timer.elapsed().as_micros()
-    );
+pub struct Day24;
+
+impl Solution for Day24 {
+    type Input<'a> = Game;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    const DAY: u8 = 24;
+    const TITLE: &'static str = "Lobby Layout";
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        Game::new(input)
+    }
+
+    fn part_1(game: &Self::Input<'_>) -> usize {
+        game.count_black_tiles()
+    }
+
+    fn part_2(game: &Self::Input<'_>) -> usize {
+        let mut game = game.clone();
+        game.run_n_days(100);
+        game.count_black_tiles()
+    }
 }
 
 #[cfg(test)]
@@ -169,15 +188,29 @@ mod tests {
     #[test]
     fn test_solution_1() {
         let input = "sesenwnenenewseeswwswswwnenewsewsw\nneeenesenwnwwswnenewnwwsewnenwseswesw\nseswneswswsenwwnwse\nnwnwneseeswswnenewneswwnewseswneseene\nswweswneswnenwsewnwneneseenw\neesenwseswswnenwswnwnwsewwnwsene\nsewnenenenesenwsewnenwwwse\nwenwwweseeeweswwwnwwe\nwsweesenenewnwwnwsenewsenwwsesesenwne\nneeswseenwwswnwswswnw\nnenwswwsewswnenenewsenwsenwnesesenew\nenewnwewneswsewnwswenweswnenwsenwsw\nsweneswneswneneenwnewenewwneswswnese\nswwesenesewenwneswnwwneseswwne\nenesenwswwswneneswsenwnewswseenwsese\nwnwnesenesenenwwnenwsewesewsesesew\nnenewswnwewswnenesenwnesewesw\neneswnwswnwsenenwnwnwwseeswneewsenese\nneswnwewnwnwseenwseesewsenwsweewe\nwseweeenwnesenwwwswnew";
-        let game = Game::new(&input);
+        let game = Game::new(input).unwrap();
         assert_eq!(game.count_black_tiles(), 10);
     }
 
     #[test]
     fn test_solution_2() {
         let input = "sesenwnenenewseeswwswswwnenewsewsw\nneeenesenwnwwswnenewnwwsewnenwseswesw\nseswneswswsenwwnwse\nnwnwneseeswswnenewneswwnewseswneseene\nswweswneswnenwsewnwneneseenw\neesenwseswswnenwswnwnwsewwnwsene\nsewnenenenesenwsewnenwwwse\nwenwwweseeeweswwwnwwe\nwsweesenenewnwwnwsenewsenwwsesesenwne\nneeswseenwwswnwswswnw\nnenwswwsewswnenenewsenwsenwnesesenew\nenewnwewneswsewnwswenweswnenwsenwsw\nsweneswneswneneenwnewenewwneswswnese\nswwesenesewenwneswnwwneseswwne\nenesenwswwswneneswsenwnewswseenwsese\nwnwnesenesenenwwnenwsewesewsesesew\nnenewswnwewswnenesenwnesewesw\neneswnwswnwsenenwnwnwwseeswneewsenese\nneswnwewnwnwseenwseesewsenwsweewe\nwseweeenwnesenwwwswnew";
-        let mut game = Game::new(&input);
+        let mut game = Game::new(input).unwrap();
         game.run_n_days(100);
         assert_eq!(game.count_black_tiles(), 2208);
     }
+
+    #[test]
+    fn test_parse_error() {
+        let input = "esew\nnwwswee\nsenwx";
+        assert_eq!(
+            Game::new(input).err().unwrap(),
+            ParseError::new(
+                3,
+                5,
+                "x",
+                "invalid direction, expected e, se, sw, w, nw or ne"
+            )
+        );
+    }
 }
@@ -1,32 +1,163 @@
+use crate::{
+    cancel::checkpoint,
+    error::{next_token, ParseError, SolveError},
+    solution::Solution,
+};
+use std::collections::HashMap;
+
 const DIVIDING_VALUE: u64 = 20201227;
+const SUBJECT_NUMBER: u64 = 7;
 
-fn transform(subject_number: u64, loop_size: usize) -> u64 {
-    let mut result = 1u64;
-    for _ in 0..loop_size {
-        result = (result * subject_number).rem_euclid(DIVIDING_VALUE);
-    }
-    result
+/// Multiplicative group modulo `modulus` in which the handshake happens, public keys are powers of
+/// `generator`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Group {
+    modulus: u64,
+    generator: u64,
 }
 
-fn get_loop_size(subject_number: u64, mut current_value: u64, target_value: u64) -> usize {
-    for loop_size in 1.. {
-        current_value = (current_value * subject_number).rem_euclid(DIVIDING_VALUE);
-        if current_value == target_value {
-            return loop_size;
+impl Group {
+    /// The group of the puzzle.
+    pub const DEFAULT: Group = Group {
+        modulus: DIVIDING_VALUE,
+        generator: SUBJECT_NUMBER,
+    };
+
+    /// Fails when the generator is not invertible modulo `modulus`.
+    pub fn new(modulus: u64, generator: u64) -> Option<Self> {
+        if modulus < 2 || inverse(generator, modulus).is_none() {
+            return None;
         }
+        Some(Self { modulus, generator })
+    }
+
+    pub fn modulus(&self) -> u64 {
+        self.modulus
+    }
+
+    pub fn generator(&self) -> u64 {
+        self.generator
+    }
+
+    fn multiply(&self, a: u64, b: u64) -> u64 {
+        (a as u128 * b as u128 % self.modulus as u128) as u64
+    }
+
+    /// `subject_number` raised to `loop_size`, by squaring.
+    pub fn transform(&self, subject_number: u64, mut loop_size: u64) -> u64 {
+        let mut base = subject_number % self.modulus;
+        let mut result = 1 % self.modulus;
+        while loop_size > 0 {
+            if loop_size & 1 == 1 {
+                result = self.multiply(result, base);
+            }
+            base = self.multiply(base, base);
+            loop_size >>= 1;
+        }
+        result
+    }
+
+    /// Smallest loop size, at least 1, transforming the generator into `public_key`.
+    ///
+    /// Baby-step giant-step : with `m` the ceiling of the square root of the modulus, the loop
+    /// size is `i * m + j` with `1 <= j <= m`, so the `m` baby steps `generator ^ j` are stored
+    /// and the giant steps look for `public_key * generator ^ (-i * m)` among them.
+    pub fn get_loop_size(&self, public_key: u64) -> Result<u64, SolveError> {
+        let step = (1..)
+            .find(|&m: &u64| m.saturating_mul(m) >= self.modulus)
+            .unwrap();
+        let mut baby_steps = HashMap::with_capacity(step as usize);
+        let mut power = 1;
+        for j in 1..=step {
+            if j % 65536 == 0 {
+                checkpoint()?;
+            }
+            power = self.multiply(power, self.generator);
+            // the smallest exponent is kept so the loop size found is the smallest one
+            baby_steps.entry(power).or_insert(j);
+        }
+
+        // power is now generator ^ step
+        let giant_step = inverse(power, self.modulus).unwrap();
+        let mut current = public_key % self.modulus;
+        for i in 0..step {
+            if i % 65536 == 0 {
+                checkpoint()?;
+            }
+            if let Some(&j) = baby_steps.get(&current) {
+                return Ok(i * step + j);
+            }
+            current = self.multiply(current, giant_step);
+        }
+        Err(SolveError::NoSolution(format!(
+            "{} is not a power of {} modulo {}",
+            public_key, self.generator, self.modulus
+        )))
     }
-    panic!("unreachable");
 }
 
-pub fn run() {
-    let timer = std::time::Instant::now();
-    let card = 15733400;
-    let door = 6408062;
-    let door_loop_size = get_loop_size(7, 1, door);
-    let encryption_key = transform(card, door_loop_size);
-    // let card_loop_size = get_loop_size(7, door, card) + door_loop_size;
-    // let encryption_key = transform(door, card_loop_size);
-    println!("day 25 solution 1 : {}, {}us", encryption_key, timer.elapsed().as_micros());
+/// Inverse of `value` modulo `modulus`, by the extended Euclidean algorithm.
+fn inverse(value: u64, modulus: u64) -> Option<u64> {
+    let (mut r0, mut r1) = (modulus as i128, (value % modulus) as i128);
+    let (mut t0, mut t1) = (0i128, 1i128);
+    while r1 != 0 {
+        let quotient = r0 / r1;
+        (r0, r1) = (r1, r0 - quotient * r1);
+        (t0, t1) = (t1, t0 - quotient * t1);
+    }
+    (r0 == 1).then(|| t0.rem_euclid(modulus as i128) as u64)
+}
+
+/// Encryption key of the handshake between the card and the door public keys.
+pub fn solve(group: &Group, card: u64, door: u64) -> Result<u64, SolveError> {
+    let door_loop_size = group.get_loop_size(door)?;
+    Ok(group.transform(card, door_loop_size))
+}
+
+/// Parses the card and door public keys of the puzzle group, one per line.
+pub fn parse_input(input: &str) -> Result<(u64, u64), ParseError> {
+    parse_keys(input, &Group::DEFAULT)
+}
+
+/// Parses the card and door public keys, one per line, both must be below the modulus.
+pub fn parse_keys(input: &str, group: &Group) -> Result<(u64, u64), ParseError> {
+    let mut lines = input.lines();
+    let mut parse_key = |message| -> Result<u64, ParseError> {
+        let key = next_token(&mut lines, input, message)?;
+        match key.parse() {
+            Ok(key) if key < group.modulus => Ok(key),
+            _ => Err(ParseError::at(
+                input,
+                key,
+                &format!("expected a public key below {}", group.modulus),
+            )),
+        }
+    };
+    let card = parse_key("expected the card public key")?;
+    let door = parse_key("expected the door public key")?;
+    Ok((card, door))
+}
+
+pub struct Day25;
+
+impl Solution for Day25 {
+    type Input<'a> = (u64, u64);
+    type Output1 = Result<u64, SolveError>;
+    type Output2 = ();
+
+    const DAY: u8 = 25;
+    const TITLE: &'static str = "Combo Breaker";
+    const PARTS: u8 = 1;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(&(card, door): &Self::Input<'_>) -> Result<u64, SolveError> {
+        solve(&Group::DEFAULT, card, door)
+    }
+
+    fn part_2(_: &Self::Input<'_>) {}
 }
 
 #[cfg(test)]
@@ -35,15 +166,54 @@ mod tests {
 
     #[test]
     fn test() {
+        let group = Group::DEFAULT;
         let card = 5764801;
         let door = 17807724;
-        let card_loop_size = get_loop_size(7, 1, card);
+        let card_loop_size = group.get_loop_size(card).unwrap();
         assert_eq!(card_loop_size, 8);
-        let door_loop_size = get_loop_size(7, card, door) + card_loop_size;
+        let door_loop_size = group.get_loop_size(door).unwrap();
         assert_eq!(door_loop_size, 11);
-        let encryption_key_1 = transform(door, card_loop_size);
-        let encryption_key_2 = transform(card, door_loop_size);
+        let encryption_key_1 = group.transform(door, card_loop_size);
+        let encryption_key_2 = group.transform(card, door_loop_size);
         assert_eq!(encryption_key_1, 14897079);
         assert_eq!(encryption_key_2, 14897079);
+        assert_eq!(solve(&group, card, door), Ok(14897079));
+        assert_eq!(group.get_loop_size(4321045), Ok(20000000));
+    }
+
+    #[test]
+    fn test_group() {
+        // 2 generates the whole group modulo 11, 3 only its squares
+        let group = Group::new(11, 2).unwrap();
+        for loop_size in 1..=10 {
+            let key = group.transform(2, loop_size);
+            assert_eq!(group.get_loop_size(key), Ok(loop_size));
+        }
+        let group = Group::new(11, 3).unwrap();
+        assert_eq!(group.get_loop_size(1), Ok(5));
+        assert!(matches!(
+            group.get_loop_size(2),
+            Err(SolveError::NoSolution(_))
+        ));
+        assert_eq!(Group::new(10, 4), None);
+        assert_eq!(Group::new(1, 1), None);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert_eq!(
+            parse_input("5764801"),
+            Err(ParseError::new(1, 8, "", "expected the door public key"))
+        );
+        let group = Group::new(11, 2).unwrap();
+        assert_eq!(
+            parse_keys("3\n11", &group),
+            Err(ParseError::new(
+                2,
+                1,
+                "11",
+                "expected a public key below 11"
+            ))
+        );
     }
 }
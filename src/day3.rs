@@ -1,8 +1,27 @@
-fn parse_input(input: &str) -> Vec<Vec<char>> {
-    input.lines().map(|s| s.chars().collect()).collect()
+use crate::{
+    error::{expect_chars, ParseError},
+    solution::Solution,
+};
+
+pub fn parse_input(input: &str) -> Result<Vec<Vec<char>>, ParseError> {
+    let width = input.lines().next().map_or(0, str::len);
+    input
+        .lines()
+        .map(|line| {
+            expect_chars(input, line, ".#")?;
+            if line.len() != width {
+                return Err(ParseError::at(
+                    input,
+                    line,
+                    &format!("expected a row of {} squares", width),
+                ));
+            }
+            Ok(line.chars().collect())
+        })
+        .collect()
 }
 
-fn get_trees_for_slope(map: &Vec<Vec<char>>, right: u8, down: u8) -> u32 {
+pub fn get_trees_for_slope(map: &[Vec<char>], right: u8, down: u8) -> u32 {
     let mut row_index = down as usize;
     let mut column_index = right as usize;
     let mut trees = 0;
@@ -16,25 +35,30 @@ fn get_trees_for_slope(map: &Vec<Vec<char>>, right: u8, down: u8) -> u32 {
     trees
 }
 
-pub fn run() {
-    let timer = std::time::Instant::now();
-    let input = std::fs::read_to_string("inputs/day3").unwrap();
-    let map = parse_input(&input);
-    let slope1 = get_trees_for_slope(&map, 1, 1);
-    let slope2 = get_trees_for_slope(&map, 3, 1);
-    let slope3 = get_trees_for_slope(&map, 5, 1);
-    let slope4 = get_trees_for_slope(&map, 7, 1);
-    let slope5 = get_trees_for_slope(&map, 1, 2);
-    println!(
-        "day 3 solution 1 : {}, {}us",
-        slope2,
-        timer.elapsed().as_micros()
-    );
-    println!(
-        "day 3 solution 2 : {}, {}us",
-        slope1 * slope2 * slope3 * slope4 * slope5,
-        timer.elapsed().as_micros()
-    );
+pub struct Day3;
+
+impl Solution for Day3 {
+    type Input<'a> = Vec<Vec<char>>;
+    type Output1 = u32;
+    type Output2 = u32;
+
+    const DAY: u8 = 3;
+    const TITLE: &'static str = "Toboggan Trajectory";
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(map: &Self::Input<'_>) -> u32 {
+        get_trees_for_slope(map, 3, 1)
+    }
+
+    fn part_2(map: &Self::Input<'_>) -> u32 {
+        [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)]
+            .iter()
+            .map(|&(right, down)| get_trees_for_slope(map, right, down))
+            .product()
+    }
 }
 
 #[cfg(test)]
@@ -44,6 +68,6 @@ mod tests {
     #[test]
     fn test_get_trees_for_slope() {
         let input = "..##.......\n#...#...#..\n.#....#..#.\n..#.#...#.#\n.#...##..#.\n..#.##.....\n.#.#.#....#\n.#........#\n#.##...#...\n#...##....#\n.#..#...#.#";
-        assert_eq!(get_trees_for_slope(&parse_input(input), 3, 1), 7);
+        assert_eq!(get_trees_for_slope(&parse_input(input).unwrap(), 3, 1), 7);
     }
 }
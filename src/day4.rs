@@ -1,3 +1,7 @@
+use crate::{
+    error::{next_token, ParseError},
+    solution::Solution,
+};
 use std::{collections::HashMap, str::FromStr};
 
 /*
@@ -14,7 +18,7 @@ use std::{collections::HashMap, str::FromStr};
 */
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
-enum PassportField {
+pub enum PassportField {
     BirthYear,
     IssueYear,
     ExpirationYear,
@@ -26,21 +30,21 @@ enum PassportField {
 }
 
 impl PassportField {
-    fn new(key: &str) -> Self {
+    pub fn new(key: &str) -> Result<Self, ParseError> {
         match key {
-            "byr" => PassportField::BirthYear,
-            "iyr" => PassportField::IssueYear,
-            "eyr" => PassportField::ExpirationYear,
-            "hgt" => PassportField::Height,
-            "hcl" => PassportField::HairColor,
-            "ecl" => PassportField::EyeColor,
-            "pid" => PassportField::PassportID,
-            "cid" => PassportField::CountryID,
-            _ => panic!("invalid passport field"),
+            "byr" => Ok(PassportField::BirthYear),
+            "iyr" => Ok(PassportField::IssueYear),
+            "eyr" => Ok(PassportField::ExpirationYear),
+            "hgt" => Ok(PassportField::Height),
+            "hcl" => Ok(PassportField::HairColor),
+            "ecl" => Ok(PassportField::EyeColor),
+            "pid" => Ok(PassportField::PassportID),
+            "cid" => Ok(PassportField::CountryID),
+            _ => Err(ParseError::at(key, key, "invalid passport field")),
         }
     }
 
-    fn is_valid_value(&self, value: &str) -> bool {
+    pub fn is_valid_value(&self, value: &str) -> bool {
         match self {
             PassportField::BirthYear if is_valid_birth_year(value) => true,
             PassportField::IssueYear if is_valid_issue_year(value) => true,
@@ -82,10 +86,7 @@ fn is_valid_hair_color(value: &str) -> bool {
 }
 
 fn is_valid_eye_color(value: &str) -> bool {
-    match value {
-        "amb" | "blu" | "brn" | "gry" | "grn" | "hzl" | "oth" => true,
-        _ => false,
-    }
+    matches!(value, "amb" | "blu" | "brn" | "gry" | "grn" | "hzl" | "oth")
 }
 
 fn is_valid_passport_id(value: &str) -> bool {
@@ -101,11 +102,11 @@ where
             return true;
         }
     }
-    return false;
+    false
 }
 
 #[derive(Debug, PartialEq)]
-struct Passport<'a> {
+pub struct Passport<'a> {
     fields: HashMap<PassportField, &'a str>,
 }
 
@@ -120,25 +121,27 @@ const REQUIRED_FIELDS: [PassportField; 7] = [
 ];
 
 impl<'a> Passport<'a> {
-    fn new(fields: &'a str) -> Self {
-        let kv_iter = fields.split('\n').map(|line| line.split(' ')).flatten();
+    pub fn new(input: &'a str) -> Result<Self, ParseError> {
+        let kv_iter = input.split('\n').flat_map(|line| line.split(' '));
         let mut fields = HashMap::new();
         for kv in kv_iter {
-            let mut split = kv.split(':');
+            let mut split = kv.splitn(2, ':');
             let key = split.next().unwrap();
-            let value = split.next().unwrap();
-            fields.insert(PassportField::new(key), value);
+            let value = next_token(&mut split, kv, "expected `:`")
+                .map_err(|error| error.within(input, kv))?;
+            let field = PassportField::new(key).map_err(|error| error.within(input, key))?;
+            fields.insert(field, value);
         }
-        Passport { fields }
+        Ok(Passport { fields })
     }
 
-    fn has_required_fields(&self) -> bool {
+    pub fn has_required_fields(&self) -> bool {
         REQUIRED_FIELDS
             .iter()
             .all(|field| self.fields.contains_key(field))
     }
 
-    fn is_valid(&self) -> bool {
+    pub fn is_valid(&self) -> bool {
         if !self.has_required_fields() {
             return false;
         }
@@ -152,11 +155,14 @@ impl<'a> Passport<'a> {
     }
 }
 
-fn parse_input(input: &str) -> Vec<Passport> {
-    input.split("\n\n").map(Passport::new).collect()
+pub fn parse_input(input: &str) -> Result<Vec<Passport<'_>>, ParseError> {
+    input
+        .split("\n\n")
+        .map(|passport| Passport::new(passport).map_err(|error| error.within(input, passport)))
+        .collect()
 }
 
-fn solve_1(passports: &Vec<Passport>) -> u32 {
+pub fn solve_1(passports: &[Passport]) -> u32 {
     passports.iter().fold(0, |acc, passport| {
         if passport.has_required_fields() {
             acc + 1
@@ -166,7 +172,7 @@ fn solve_1(passports: &Vec<Passport>) -> u32 {
     })
 }
 
-fn solve_2(passports: &Vec<Passport>) -> u32 {
+pub fn solve_2(passports: &[Passport]) -> u32 {
     passports.iter().fold(
         0,
         |acc, passport| {
@@ -179,20 +185,27 @@ fn solve_2(passports: &Vec<Passport>) -> u32 {
     )
 }
 
-pub fn run() {
-    let timer = std::time::Instant::now();
-    let input = std::fs::read_to_string("inputs/day4").unwrap();
-    let passports = parse_input(&input);
-    println!(
-        "day 4 solution 1 : {}, {}us",
-        solve_1(&passports),
-        timer.elapsed().as_micros()
-    );
-    println!(
-        "day 4 solution 2 : {}, {}us",
-        solve_2(&passports),
-        timer.elapsed().as_micros()
-    );
+pub struct Day4;
+
+impl Solution for Day4 {
+    type Input<'a> = Vec<Passport<'a>>;
+    type Output1 = u32;
+    type Output2 = u32;
+
+    const DAY: u8 = 4;
+    const TITLE: &'static str = "Passport Processing";
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(passports: &Self::Input<'_>) -> u32 {
+        solve_1(passports)
+    }
+
+    fn part_2(passports: &Self::Input<'_>) -> u32 {
+        solve_2(passports)
+    }
 }
 
 #[cfg(test)]
@@ -201,13 +214,13 @@ mod tests {
 
     #[test]
     fn test_new_passport_field() {
-        assert_eq!(PassportField::BirthYear, PassportField::new("byr"));
+        assert_eq!(PassportField::BirthYear, PassportField::new("byr").unwrap());
     }
 
     #[test]
     fn test_new_passport() {
         assert_eq!(
-            Passport::new("byr:2020\neyr:2030 hcl:#000000"),
+            Passport::new("byr:2020\neyr:2030 hcl:#000000").unwrap(),
             Passport {
                 fields: [
                     (PassportField::BirthYear, "2020"),
@@ -221,21 +234,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_error() {
+        let input = "byr:2020\neyr:2030 hcl:#000000\n\nbyr:2020 eyr2030";
+        assert_eq!(
+            parse_input(input).err().unwrap(),
+            ParseError::new(4, 17, "", "expected `:`")
+        );
+        let input = "byr:2020\neyr:2030 hcl:#000000\n\nbyr:2020 xyz:2030";
+        assert_eq!(
+            parse_input(input).err().unwrap(),
+            ParseError::new(4, 10, "xyz", "invalid passport field")
+        );
+    }
+
     #[test]
     fn test_solution_1() {
         let input = "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd\nbyr:1937 iyr:2017 cid:147 hgt:183cm\n\niyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884\nhcl:#cfa07d byr:1929\n\nhcl:#ae17e1 iyr:2013\neyr:2024\necl:brn pid:760753108 byr:1931\nhgt:179cm\n\nhcl:#cfa07d eyr:2025 pid:166559648\niyr:2011 ecl:brn hgt:59in";
-        assert_eq!(solve_1(&parse_input(&input)), 2);
+        assert_eq!(solve_1(&parse_input(input).unwrap()), 2);
     }
 
     #[test]
     fn test_solution_2_valid() {
         let input = "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980\nhcl:#623a2f\n\neyr:2029 ecl:blu cid:129 byr:1989\niyr:2014 pid:896056539 hcl:#a97842 hgt:165cm\n\nhcl:#888785\nhgt:164cm byr:2001 iyr:2015 cid:88\npid:545766238 ecl:hzl\neyr:2022\n\niyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719";
-        assert_eq!(solve_2(&parse_input(&input)), 4);
+        assert_eq!(solve_2(&parse_input(input).unwrap()), 4);
     }
 
     #[test]
     fn test_solution_2_invalid() {
         let input = "eyr:1972 cid:100\nhcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926\n\niyr:2019\nhcl:#602927 eyr:1967 hgt:170cm\necl:grn pid:012533040 byr:1946\n\nhcl:dab227 iyr:2012\necl:brn hgt:182cm pid:021572410 eyr:2020 byr:1992 cid:277\n\nhgt:59cm ecl:zzz\neyr:2038 hcl:74454a iyr:2023\npid:3556412378 byr:2007";
-        assert_eq!(solve_2(&parse_input(&input)), 0);
+        assert_eq!(solve_2(&parse_input(input).unwrap()), 0);
     }
 }
@@ -1,10 +1,18 @@
+use crate::{
+    error::{expect_chars, ParseError, SolveError},
+    solution::Solution,
+};
 use std::collections::HashSet;
 
-fn parse_input(input: &str) -> HashSet<u16> {
+pub fn parse_input(input: &str) -> Result<HashSet<u16>, ParseError> {
     input
         .lines()
         .map(|s| {
-            s.chars()
+            expect_chars(input, s, "FBLR")?;
+            if s.len() != 10 {
+                return Err(ParseError::at(input, s, "expected a seat of 10 characters"));
+            }
+            Ok(s.chars()
                 .fold(String::with_capacity(s.len()), |mut acc, c| {
                     match c {
                         'F' | 'L' => {
@@ -16,29 +24,72 @@ fn parse_input(input: &str) -> HashSet<u16> {
                         _ => {}
                     }
                     acc
-                })
+                }))
         })
-        .map(|s| u16::from_str_radix(&s, 2).unwrap())
+        .map(|s| s.map(|s| u16::from_str_radix(&s, 2).unwrap()))
         .collect()
 }
 
-fn solve_1(numbers: &HashSet<u16>) -> u16 {
-    *numbers.iter().max().unwrap()
+pub fn solve_1(numbers: &HashSet<u16>) -> Result<u16, SolveError> {
+    numbers
+        .iter()
+        .max()
+        .copied()
+        .ok_or_else(|| SolveError::NoSolution("no boarding pass".to_owned()))
 }
 
-fn solve_2(numbers: &HashSet<u16>, max: u16) -> u16 {
-    let mut i = max;
-    while numbers.contains(&i) {
-        i -= 1;
+/// The first free seat below `max`.
+pub fn solve_2(numbers: &HashSet<u16>, max: u16) -> Result<u16, SolveError> {
+    (0..max)
+        .rev()
+        .find(|seat| !numbers.contains(seat))
+        .ok_or_else(|| SolveError::NoSolution("every seat is taken".to_owned()))
+}
+
+pub struct Day5;
+
+impl Solution for Day5 {
+    type Input<'a> = HashSet<u16>;
+    type Output1 = Result<u16, SolveError>;
+    type Output2 = Result<u16, SolveError>;
+
+    const DAY: u8 = 5;
+    const TITLE: &'static str = "Binary Boarding";
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(numbers: &Self::Input<'_>) -> Result<u16, SolveError> {
+        solve_1(numbers)
+    }
+
+    fn part_2(numbers: &Self::Input<'_>) -> Result<u16, SolveError> {
+        solve_2(numbers, solve_1(numbers)?)
     }
-    i
 }
 
-pub fn run() {
-    let timer = std::time::Instant::now();
-    let input = std::fs::read_to_string("inputs/day5").unwrap();
-    let numbers = parse_input(&input);
-    let max = solve_1(&numbers);
-    println!("day 5 solution 1 : {}, {}us", max, timer.elapsed().as_micros());
-    println!("day 5 solution 2 : {}, {}us", solve_2(&numbers, max), timer.elapsed().as_micros());
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solution() {
+        let numbers = parse_input("FFFFFFFLLR\nFFFFFFFLRL\nFFFFFFFRLL").unwrap();
+        assert_eq!(solve_1(&numbers), Ok(4));
+        assert_eq!(solve_2(&numbers, 4), Ok(3));
+    }
+
+    #[test]
+    fn test_no_solution() {
+        let numbers = parse_input("FFFFFFFLLL\nFFFFFFFLLR").unwrap();
+        assert!(matches!(
+            solve_2(&numbers, 1),
+            Err(SolveError::NoSolution(_))
+        ));
+        assert!(matches!(
+            solve_1(&parse_input("").unwrap()),
+            Err(SolveError::NoSolution(_))
+        ));
+    }
 }
@@ -1,13 +1,27 @@
+use crate::{
+    error::{expect_chars, ParseError, SolveError},
+    solution::Solution,
+};
 use std::collections::HashSet;
 
-fn parse_input(input: &str) -> Vec<Vec<&str>> {
+pub fn parse_input(input: &str) -> Result<Vec<Vec<&str>>, ParseError> {
     input
         .split("\n\n")
-        .map(|s| s.lines().collect::<Vec<_>>())
-        .collect::<Vec<_>>()
+        .map(|s| {
+            s.lines()
+                .map(|line| {
+                    if line.is_empty() {
+                        return Err(ParseError::at(input, line, "expected answers"));
+                    }
+                    expect_chars(input, line, "abcdefghijklmnopqrstuvwxyz")?;
+                    Ok(line)
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()
 }
 
-fn solve_1(answers: &Vec<Vec<&str>>) -> usize {
+pub fn solve_1(answers: &[Vec<&str>]) -> usize {
     answers.iter().fold(0, |total, answer| {
         total
             + answer
@@ -22,30 +36,65 @@ fn solve_1(answers: &Vec<Vec<&str>>) -> usize {
     })
 }
 
-fn solve_2(answers: &Vec<Vec<&str>>) -> usize {
-    answers.iter().fold(0, |total, answer| {
-        total
-            + answer[1..]
-                .iter()
-                .fold(answer[0].chars().collect::<Vec<_>>(), |vec, &s| {
-                    vec.iter().filter(|&c| s.contains(*c)).cloned().collect()
-                })
-                .len()
-    })
+/// An empty group has no answers to compare.
+pub fn solve_2(answers: &[Vec<&str>]) -> Result<usize, SolveError> {
+    answers
+        .iter()
+        .enumerate()
+        .try_fold(0, |total, (index, answer)| {
+            let (first, others) = answer.split_first().ok_or_else(|| {
+                SolveError::NoSolution(format!("group {} has no answers", index + 1))
+            })?;
+            Ok(total
+                + others
+                    .iter()
+                    .fold(first.chars().collect::<Vec<_>>(), |vec, &s| {
+                        vec.iter().filter(|&c| s.contains(*c)).cloned().collect()
+                    })
+                    .len())
+        })
+}
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    type Input<'a> = Vec<Vec<&'a str>>;
+    type Output1 = usize;
+    type Output2 = Result<usize, SolveError>;
+
+    const DAY: u8 = 6;
+    const TITLE: &'static str = "Custom Customs";
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(answers: &Self::Input<'_>) -> usize {
+        solve_1(answers)
+    }
+
+    fn part_2(answers: &Self::Input<'_>) -> Result<usize, SolveError> {
+        solve_2(answers)
+    }
 }
 
-pub fn run() {
-    let timer = std::time::Instant::now();
-    let input = std::fs::read_to_string("inputs/day6").unwrap();
-    let answers = parse_input(&input);
-    println!(
-        "day 6 solution 1 : {}, {}us",
-        solve_1(&answers),
-        timer.elapsed().as_micros()
-    );
-    println!(
-        "day 6 solution 2 : {}, {}us",
-        solve_2(&answers),
-        timer.elapsed().as_micros()
-    );
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solution() {
+        let answers = parse_input("abc\n\na\nb\nc\n\nab\nac\n\na\na\na\na\n\nb").unwrap();
+        assert_eq!(solve_1(&answers), 11);
+        assert_eq!(solve_2(&answers), Ok(6));
+    }
+
+    #[test]
+    fn test_no_solution() {
+        let answers = parse_input("ab\n\n\n\nb").unwrap();
+        assert_eq!(
+            solve_2(&answers),
+            Err(SolveError::NoSolution("group 2 has no answers".to_owned()))
+        );
+    }
 }
@@ -1,8 +1,12 @@
+use crate::{
+    error::{next_token, parse_token, ParseError, SolveError},
+    solution::Solution,
+};
 use std::collections::{HashMap, HashSet};
 
-struct Bag<'a> {
-    parents: HashSet<&'a str>,
-    children: HashMap<&'a str, u8>,
+pub struct Bag<'a> {
+    pub parents: HashSet<&'a str>,
+    pub children: HashMap<&'a str, u8>,
 }
 
 impl<'a> Bag<'a> {
@@ -14,27 +18,29 @@ impl<'a> Bag<'a> {
     }
 }
 
-fn parse_input(input: &str) -> HashMap<&str, Bag> {
+pub fn parse_input(input: &str) -> Result<HashMap<&str, Bag<'_>>, ParseError> {
     let mut bag_map: HashMap<&str, Bag> = HashMap::new();
     for line in input.lines().map(|s| s.trim()) {
         let mut first_split = line.split(" bags contain ");
         let bag_color = first_split.next().unwrap();
-        let rest = first_split.next().unwrap();
+        let rest = next_token(&mut first_split, line, "expected ` bags contain `")
+            .map_err(|error| error.within(input, line))?;
 
         if !bag_map.contains_key(bag_color) {
             bag_map.insert(bag_color, Bag::new());
         }
 
         if rest != "no other bags." {
-            let mut second_split = rest.split(", ");
-            while let Some(content) = second_split.next() {
+            let second_split = rest.split(", ");
+            for content in second_split {
                 let mut third_split = content
                     .trim_end_matches('.')
                     .trim_end_matches('s')
                     .trim_end_matches(" bag")
                     .splitn(2, ' ');
-                let count = third_split.next().unwrap().parse().unwrap();
-                let inner_bag_color = third_split.next().unwrap();
+                let count = parse_token(input, third_split.next().unwrap(), "expected a count")?;
+                let inner_bag_color = next_token(&mut third_split, content, "expected a bag color")
+                    .map_err(|error| error.within(input, content))?;
 
                 {
                     let bag = bag_map.get_mut(bag_color).unwrap();
@@ -51,13 +57,13 @@ fn parse_input(input: &str) -> HashMap<&str, Bag> {
             }
         }
     }
-    bag_map
+    Ok(bag_map)
 }
 
-fn solve_1(bag_map: &HashMap<&str, Bag>) -> usize {
+pub fn solve_1(bag_map: &HashMap<&str, Bag>) -> usize {
     let mut seen = HashSet::new();
     let mut colors = vec!["shiny gold"];
-    while colors.len() > 0 {
+    while !colors.is_empty() {
         let mut new_colors = vec![];
         for color in colors {
             if let Some(bag) = bag_map.get(color) {
@@ -74,31 +80,60 @@ fn solve_1(bag_map: &HashMap<&str, Bag>) -> usize {
     seen.len()
 }
 
-fn solve_2(bag_map: &HashMap<&str, Bag>, bag_color: &str) -> usize {
-    let bag_contents = bag_map.get(bag_color).unwrap();
-    if bag_contents.children.len() > 0 {
-        bag_contents.children.iter().fold(0, |acc, (&key, &value)| {
-            acc + value as usize * (solve_2(bag_map, key) + 1)
-        })
-    } else {
-        0
+/// Number of bags inside `bag_color`, which cannot be counted when a bag contains itself.
+pub fn solve_2(bag_map: &HashMap<&str, Bag>, bag_color: &str) -> Result<usize, SolveError> {
+    count_inside(bag_map, bag_color, &mut vec![])
+}
+
+/// `path` holds the bags containing the current one.
+fn count_inside<'b>(
+    bag_map: &'b HashMap<&str, Bag>,
+    bag_color: &'b str,
+    path: &mut Vec<&'b str>,
+) -> Result<usize, SolveError> {
+    let bag_contents = bag_map
+        .get(bag_color)
+        .ok_or_else(|| SolveError::NoSolution(format!("no rule for {} bags", bag_color)))?;
+    if path.contains(&bag_color) {
+        return Err(SolveError::NoSolution(format!(
+            "{} bags contain themselves",
+            bag_color
+        )));
     }
+    path.push(bag_color);
+    let mut total = 0usize;
+    for (&key, &value) in &bag_contents.children {
+        total = count_inside(bag_map, key, path)?
+            .checked_add(1)
+            .and_then(|count| count.checked_mul(value as usize))
+            .and_then(|count| count.checked_add(total))
+            .ok_or_else(|| SolveError::NoSolution("too many bags to count".to_owned()))?;
+    }
+    path.pop();
+    Ok(total)
 }
 
-pub fn run() {
-    let timer = std::time::Instant::now();
-    let input = std::fs::read_to_string("inputs/day7").unwrap();
-    let parsed_input = parse_input(&input);
-    println!(
-        "day 7 solution 1 : {}, {}us",
-        solve_1(&parsed_input),
-        timer.elapsed().as_micros()
-    );
-    println!(
-        "day 7 solution 2 : {}, {}us",
-        solve_2(&parsed_input, "shiny gold"),
-        timer.elapsed().as_micros()
-    );
+pub struct Day7;
+
+impl Solution for Day7 {
+    type Input<'a> = HashMap<&'a str, Bag<'a>>;
+    type Output1 = usize;
+    type Output2 = Result<usize, SolveError>;
+
+    const DAY: u8 = 7;
+    const TITLE: &'static str = "Handy Haversacks";
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(bag_map: &Self::Input<'_>) -> usize {
+        solve_1(bag_map)
+    }
+
+    fn part_2(bag_map: &Self::Input<'_>) -> Result<usize, SolveError> {
+        solve_2(bag_map, "shiny gold")
+    }
 }
 
 #[cfg(test)]
@@ -108,12 +143,29 @@ mod tests {
     #[test]
     fn test_solution_1() {
         let input = "light red bags contain 1 bright white bag, 2 muted yellow bags.\ndark orange bags contain 3 bright white bags, 4 muted yellow bags.\nbright white bags contain 1 shiny gold bag.\nmuted yellow bags contain 2 shiny gold bags, 9 faded blue bags.\nshiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.\ndark olive bags contain 3 faded blue bags, 4 dotted black bags.\nvibrant plum bags contain 5 faded blue bags, 6 dotted black bags.\nfaded blue bags contain no other bags.\ndotted black bags contain no other bags.";
-        assert_eq!(solve_1(&parse_input(input)), 4);
+        assert_eq!(solve_1(&parse_input(input).unwrap()), 4);
     }
 
     #[test]
     fn test_solution_2() {
         let input = "shiny gold bags contain 2 dark red bags.\ndark red bags contain 2 dark orange bags.\ndark orange bags contain 2 dark yellow bags.\ndark yellow bags contain 2 dark green bags.\ndark green bags contain 2 dark blue bags.\ndark blue bags contain 2 dark violet bags.\ndark violet bags contain no other bags.";
-        assert_eq!(solve_2(&parse_input(input), "shiny gold"), 126);
+        assert_eq!(solve_2(&parse_input(input).unwrap(), "shiny gold"), Ok(126));
+    }
+
+    #[test]
+    fn test_no_solution() {
+        let input =
+            "shiny gold bags contain 2 dark red bags.\ndark red bags contain 1 shiny gold bag.";
+        assert_eq!(
+            solve_2(&parse_input(input).unwrap(), "shiny gold"),
+            Err(SolveError::NoSolution(
+                "shiny gold bags contain themselves".to_owned()
+            ))
+        );
+        let input = "dark red bags contain no other bags.";
+        assert!(matches!(
+            solve_2(&parse_input(input).unwrap(), "shiny gold"),
+            Err(SolveError::NoSolution(_))
+        ));
     }
 }
@@ -1,94 +1,450 @@
-use std::collections::HashSet;
+use crate::{
+    error::{next_token, parse_token, ParseError, SolveError},
+    handheld::{InstructionSet, Machine, Registers},
+    solution::Solution,
+};
+use std::{convert::TryFrom, fmt::Display};
 
-#[derive(Debug, PartialEq)]
-enum Instruction {
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Instruction {
     Acc(i32),
     Jmp(i32),
     Nop(i32),
 }
 
 impl Instruction {
-    fn new(instruction: &str) -> Self {
-        let mut split = instruction.split(' ');
+    pub fn new(instruction: &str) -> Result<Self, ParseError> {
+        let mut split = instruction.splitn(2, ' ');
         let operation = split.next().unwrap();
-        let argument = split.next().unwrap().parse().unwrap();
+        let argument = next_token(&mut split, instruction, "expected an argument")?;
+        let argument = parse_token(instruction, argument, "invalid argument")?;
         match operation {
-            "acc" => Instruction::Acc(argument),
-            "jmp" => Instruction::Jmp(argument),
-            "nop" => Instruction::Nop(argument),
-            _ => panic!("invalid operation"),
+            "acc" => Ok(Instruction::Acc(argument)),
+            "jmp" => Ok(Instruction::Jmp(argument)),
+            "nop" => Ok(Instruction::Nop(argument)),
+            _ => Err(ParseError::at(
+                instruction,
+                operation,
+                "invalid operation, expected acc, jmp or nop",
+            )),
+        }
+    }
+
+    /// The instruction with `jmp` and `nop` swapped, `acc` cannot be corrupted.
+    pub fn flipped(&self) -> Option<Self> {
+        match *self {
+            Instruction::Acc(_) => None,
+            Instruction::Jmp(arg) => Some(Instruction::Nop(arg)),
+            Instruction::Nop(arg) => Some(Instruction::Jmp(arg)),
+        }
+    }
+
+    /// Index of the instruction run after this one, found at `index`.
+    pub fn next_index(&self, index: usize) -> i64 {
+        match *self {
+            Instruction::Jmp(arg) => index as i64 + arg as i64,
+            Instruction::Acc(_) | Instruction::Nop(_) => index as i64 + 1,
+        }
+    }
+
+    fn acc_delta(&self) -> i64 {
+        match *self {
+            Instruction::Acc(arg) => arg as i64,
+            Instruction::Jmp(_) | Instruction::Nop(_) => 0,
         }
     }
 }
 
-fn parse_input(input: &str) -> Vec<Instruction> {
-    input.lines().map(Instruction::new).collect()
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Instruction::Acc(arg) => write!(f, "acc {:+}", arg),
+            Instruction::Jmp(arg) => write!(f, "jmp {:+}", arg),
+            Instruction::Nop(arg) => write!(f, "nop {:+}", arg),
+        }
+    }
 }
 
-fn run_instructions(instructions: &Vec<Instruction>, swap_index: Option<usize>) -> (i32, usize) {
-    let mut acc = 0;
-    let mut index = 0;
-    let mut seen = HashSet::new();
+impl InstructionSet for Instruction {
+    fn parse(line: &str) -> Result<Self, ParseError> {
+        Instruction::new(line)
+    }
 
-    while !seen.contains(&index) && index < instructions.len() {
-        seen.insert(index);
-        match instructions[index] {
+    fn execute(&self, registers: &mut Registers) {
+        match *self {
             Instruction::Acc(arg) => {
-                acc += arg;
-                index += 1;
+                registers.acc += arg as i64;
+                registers.ip += 1;
             }
-            Instruction::Jmp(arg) => {
-                if swap_index == Some(index) {
-                    index += 1;
-                } else {
-                    index = (index as i32 + arg) as usize;
+            Instruction::Jmp(arg) => registers.ip += arg as i64,
+            Instruction::Nop(_) => registers.ip += 1,
+        }
+    }
+}
+
+pub fn parse_input(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    Ok(Machine::load(input)?.program().to_vec())
+}
+
+pub fn solve_1(instructions: &[Instruction]) -> i64 {
+    let mut machine = Machine::new(instructions.to_vec());
+    machine.run();
+    machine.acc()
+}
+
+/// Flip of a `jmp` or a `nop` making the program terminate, with the accumulator at the end.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Repair {
+    pub index: usize,
+    pub replacement: Instruction,
+    pub acc: i64,
+}
+
+/// For every instruction which reaches the end of the program, the accumulator gained on the
+/// way. The index after the last instruction is the end.
+///
+/// Every instruction has a single successor so walking the predecessors back from the end visits
+/// each of them at most once.
+pub fn acc_to_end(instructions: &[Instruction]) -> Vec<Option<i64>> {
+    let end = instructions.len();
+    let mut predecessors = vec![vec![]; end + 1];
+    for (index, instruction) in instructions.iter().enumerate() {
+        let next = instruction.next_index(index);
+        if (0..=end as i64).contains(&next) {
+            predecessors[next as usize].push(index);
+        }
+    }
+
+    let mut acc_to_end = vec![None; end + 1];
+    acc_to_end[end] = Some(0);
+    let mut queue = vec![end];
+    while let Some(index) = queue.pop() {
+        let acc = acc_to_end[index].unwrap();
+        for &predecessor in &predecessors[index] {
+            acc_to_end[predecessor] = Some(instructions[predecessor].acc_delta() + acc);
+            queue.push(predecessor);
+        }
+    }
+    acc_to_end
+}
+
+/// Every flip making the program terminate, in the order the instructions run.
+///
+/// Flipping an instruction which does not run changes nothing, so only the instructions run
+/// before the loop are candidates, and none when the program already terminates. A flip works
+/// when the new next instruction reaches the end : the path from there cannot go through the
+/// flipped instruction, which does not reach the end.
+pub fn find_repairs(instructions: &[Instruction]) -> Vec<Repair> {
+    let end = instructions.len();
+    let acc_to_end = acc_to_end(instructions);
+    let reaching_end = |index: i64| {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| acc_to_end.get(index).copied().flatten())
+    };
+
+    let mut repairs = vec![];
+    let mut visited = vec![false; end];
+    let mut acc = 0;
+    let mut index = 0;
+    while index < end && !visited[index] {
+        visited[index] = true;
+        let instruction = instructions[index];
+        if acc_to_end[index].is_some() {
+            // the program terminates without any flip
+            break;
+        }
+        if let Some(replacement) = instruction.flipped() {
+            if let Some(rest) = reaching_end(replacement.next_index(index)) {
+                repairs.push(Repair {
+                    index,
+                    replacement,
+                    acc: acc + rest,
+                });
+            }
+        }
+        acc += instruction.acc_delta();
+        match usize::try_from(instruction.next_index(index)) {
+            Ok(next) => index = next,
+            Err(_) => break,
+        }
+    }
+    repairs
+}
+
+/// The first repair in the order the instructions run.
+pub fn solve_2(instructions: &[Instruction]) -> Result<i64, SolveError> {
+    find_repairs(instructions)
+        .first()
+        .map(|repair| repair.acc)
+        .ok_or_else(|| {
+            SolveError::NoSolution("no swapped jmp or nop makes the program terminate".to_owned())
+        })
+}
+
+/// Where the control goes after the last instruction of a block.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Exit {
+    Block(usize),
+    End,
+    /// Jump out of the program, to the given index.
+    Fault(i64),
+}
+
+/// Instructions from `start` to `end` (excluded) always running in sequence.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Block {
+    pub start: usize,
+    pub end: usize,
+    pub exit: Exit,
+}
+
+/// Control flow of a program split in basic blocks, shown as a listing with `Display`.
+pub struct Disassembly<'a> {
+    instructions: &'a [Instruction],
+    pub blocks: Vec<Block>,
+    /// Whether the block runs when starting from the first instruction.
+    pub reachable: Vec<bool>,
+    /// The blocks of every cycle of the graph, in the order they run.
+    pub loops: Vec<Vec<usize>>,
+    block_of: Vec<usize>,
+}
+
+impl<'a> Disassembly<'a> {
+    pub fn new(instructions: &'a [Instruction]) -> Self {
+        let end = instructions.len();
+        // blocks start at the first instruction, at jump targets and after jumps
+        let mut leaders = vec![false; end + 1];
+        leaders[0] = true;
+        for (index, instruction) in instructions.iter().enumerate() {
+            if let Instruction::Jmp(_) = instruction {
+                leaders[index + 1] = true;
+                if let Ok(target) = usize::try_from(instruction.next_index(index)) {
+                    if target < end {
+                        leaders[target] = true;
+                    }
                 }
             }
-            Instruction::Nop(arg) => {
-                if swap_index == Some(index) {
-                    index = (index as i32 + arg) as usize;
-                } else {
-                    index += 1;
+        }
+
+        let mut block_of = vec![0; end];
+        let mut starts = vec![];
+        for index in 0..end {
+            if leaders[index] {
+                starts.push(index);
+            }
+            block_of[index] = starts.len() - 1;
+        }
+        let blocks = starts
+            .iter()
+            .enumerate()
+            .map(|(block, &start)| {
+                let block_end = starts.get(block + 1).copied().unwrap_or(end);
+                let next = instructions[block_end - 1].next_index(block_end - 1);
+                let exit = match usize::try_from(next) {
+                    Ok(next) if next == end => Exit::End,
+                    Ok(next) if next < end => Exit::Block(block_of[next]),
+                    _ => Exit::Fault(next),
+                };
+                Block {
+                    start,
+                    end: block_end,
+                    exit,
                 }
+            })
+            .collect::<Vec<_>>();
+
+        let mut reachable = vec![false; blocks.len()];
+        let mut block = (!blocks.is_empty()).then_some(0);
+        while let Some(current) = block.filter(|&current| !reachable[current]) {
+            reachable[current] = true;
+            block = match blocks[current].exit {
+                Exit::Block(next) => Some(next),
+                Exit::End | Exit::Fault(_) => None,
+            };
+        }
+
+        Self {
+            instructions,
+            loops: find_loops(&blocks),
+            blocks,
+            reachable,
+            block_of,
+        }
+    }
+
+    /// The block containing the instruction at `index`.
+    pub fn block_of(&self, index: usize) -> usize {
+        self.block_of[index]
+    }
+
+    /// Index in `loops` of the loop the block belongs to, if any.
+    pub fn loop_of(&self, block: usize) -> Option<usize> {
+        self.loops.iter().position(|blocks| blocks.contains(&block))
+    }
+
+    fn exit_name(exit: Exit) -> String {
+        match exit {
+            Exit::Block(block) => format!("L{}", block),
+            Exit::End => "end".to_owned(),
+            Exit::Fault(index) => format!("fault {}", index),
+        }
+    }
+
+    /// The control flow graph in the Graphviz format : unreachable blocks are dashed and the
+    /// edges of the loops are red.
+    pub fn to_dot(&self) -> String {
+        let mut dot =
+            String::from("digraph program {\n    node [shape=box, fontname=\"monospace\"];\n");
+        dot.push_str("    end [shape=doublecircle];\n");
+        for (index, block) in self.blocks.iter().enumerate() {
+            let mut label = format!("L{}\\l", index);
+            for line in block.start..block.end {
+                label.push_str(&format!("{}: {}\\l", line, self.instructions[line]));
             }
+            let style = if self.reachable[index] {
+                ""
+            } else {
+                ", style=dashed"
+            };
+            dot.push_str(&format!("    L{} [label=\"{}\"{}];\n", index, label, style));
         }
+        for (index, block) in self.blocks.iter().enumerate() {
+            let target = match block.exit {
+                Exit::Block(next) => format!("L{}", next),
+                Exit::End => "end".to_owned(),
+                Exit::Fault(next) => {
+                    dot.push_str(&format!(
+                        "    fault{} [shape=octagon, label=\"fault {}\"];\n",
+                        index, next
+                    ));
+                    format!("fault{}", index)
+                }
+            };
+            let in_loop = matches!(block.exit, Exit::Block(next)
+                if self.loop_of(index).is_some() && self.loop_of(index) == self.loop_of(next));
+            let style = if in_loop { " [color=red]" } else { "" };
+            dot.push_str(&format!("    L{} -> {}{};\n", index, target, style));
+        }
+        dot.push_str("}\n");
+        dot
     }
-    (acc, index)
 }
 
-fn solve_1(instructions: &Vec<Instruction>) -> i32 {
-    run_instructions(instructions, None).0
+/// Cycles of the graph, where every block has a single successor : following the exits from each
+/// block not seen yet either meets a block of a previous walk or closes a new cycle.
+fn find_loops(blocks: &[Block]) -> Vec<Vec<usize>> {
+    let mut walk_of = vec![None; blocks.len()];
+    let mut loops = vec![];
+    for first in 0..blocks.len() {
+        let mut block = first;
+        let mut path = vec![];
+        while walk_of[block].is_none() {
+            walk_of[block] = Some(first);
+            path.push(block);
+            match blocks[block].exit {
+                Exit::Block(next) => block = next,
+                Exit::End | Exit::Fault(_) => break,
+            }
+        }
+        if walk_of[block] == Some(first) && matches!(blocks[block].exit, Exit::Block(_)) {
+            if let Some(position) = path.iter().position(|&current| current == block) {
+                loops.push(path[position..].to_vec());
+            }
+        }
+    }
+    loops
 }
 
-fn solve_2(instructions: &Vec<Instruction>) -> i32 {
-    for swap_index in 0..instructions.len() {
-        if let Instruction::Acc(_) = instructions[swap_index] {
-            continue;
+impl Display for Disassembly<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reachable_instructions = self
+            .blocks
+            .iter()
+            .zip(&self.reachable)
+            .filter(|(_, &reachable)| reachable)
+            .map(|(block, _)| block.end - block.start)
+            .sum::<usize>();
+        let count = |is_operation: fn(&Instruction) -> bool| {
+            self.instructions
+                .iter()
+                .filter(|instruction| is_operation(instruction))
+                .count()
+        };
+        writeln!(
+            f,
+            "; {} instructions ({} acc, {} jmp, {} nop), {} reachable, {} blocks, {} loop(s)",
+            self.instructions.len(),
+            count(|instruction| matches!(instruction, Instruction::Acc(_))),
+            count(|instruction| matches!(instruction, Instruction::Jmp(_))),
+            count(|instruction| matches!(instruction, Instruction::Nop(_))),
+            reachable_instructions,
+            self.blocks.len(),
+            self.loops.len()
+        )?;
+        for (index, blocks) in self.loops.iter().enumerate() {
+            let names = blocks
+                .iter()
+                .map(|&block| format!("L{}", block))
+                .collect::<Vec<_>>();
+            writeln!(f, "; loop {} : {}", index + 1, names.join(" -> "))?;
         }
-
-        let (acc, index) = run_instructions(instructions, Some(swap_index));
-
-        if index == instructions.len() {
-            return acc;
+        for (index, block) in self.blocks.iter().enumerate() {
+            let mut notes = vec![];
+            if !self.reachable[index] {
+                notes.push("unreachable".to_owned());
+            }
+            if let Some(index) = self.loop_of(index) {
+                notes.push(format!("loop {}", index + 1));
+            }
+            if notes.is_empty() {
+                writeln!(f, "L{}:", index)?;
+            } else {
+                writeln!(f, "L{}: ; {}", index, notes.join(", "))?;
+            }
+            for line in block.start..block.end {
+                let instruction = self.instructions[line];
+                // the exit is shown for jumps and when it is not the next block
+                let falls_through = block.exit == Exit::Block(index + 1)
+                    && !matches!(instruction, Instruction::Jmp(_));
+                if line + 1 == block.end && !falls_through {
+                    writeln!(
+                        f,
+                        "{:>6}  {:<8} ; -> {}",
+                        line,
+                        instruction.to_string(),
+                        Self::exit_name(block.exit)
+                    )?;
+                } else {
+                    writeln!(f, "{:>6}  {}", line, instruction)?;
+                }
+            }
         }
+        Ok(())
     }
-    panic!("solution not found");
 }
 
-pub fn run() {
-    let timer = std::time::Instant::now();
-    let input = std::fs::read_to_string("inputs/day8").unwrap();
-    let instructions = parse_input(&input);
-    println!(
-        "day 8 solution 1 : {}, {}us",
-        solve_1(&instructions),
-        timer.elapsed().as_micros()
-    );
-    println!(
-        "day 8 solution 2 : {}, {}us",
-        solve_2(&instructions),
-        timer.elapsed().as_micros()
-    );
+pub struct Day8;
+
+impl Solution for Day8 {
+    type Input<'a> = Vec<Instruction>;
+    type Output1 = i64;
+    type Output2 = Result<i64, SolveError>;
+
+    const DAY: u8 = 8;
+    const TITLE: &'static str = "Handheld Halting";
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(instructions: &Self::Input<'_>) -> i64 {
+        solve_1(instructions)
+    }
+
+    fn part_2(instructions: &Self::Input<'_>) -> Result<i64, SolveError> {
+        solve_2(instructions)
+    }
 }
 
 #[cfg(test)]
@@ -97,20 +453,139 @@ mod tests {
 
     #[test]
     fn test_instruction_parsing() {
-        assert_eq!(Instruction::new("acc -3"), Instruction::Acc(-3));
-        assert_eq!(Instruction::new("jmp 3"), Instruction::Jmp(3));
-        assert_eq!(Instruction::new("nop 12"), Instruction::Nop(12));
+        assert_eq!(Instruction::new("acc -3").unwrap(), Instruction::Acc(-3));
+        assert_eq!(Instruction::new("jmp 3").unwrap(), Instruction::Jmp(3));
+        assert_eq!(Instruction::new("nop 12").unwrap(), Instruction::Nop(12));
+        assert_eq!(Instruction::Jmp(-4).to_string(), "jmp -4");
+        assert_eq!(Instruction::Nop(0).to_string(), "nop +0");
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let input = "nop +0\nacc +1\nfoo +4\nacc +3";
+        assert_eq!(
+            parse_input(input).err().unwrap(),
+            ParseError::new(3, 1, "foo", "invalid operation, expected acc, jmp or nop")
+        );
+        let input = "nop +0\nacc +x";
+        assert_eq!(
+            parse_input(input).err().unwrap(),
+            ParseError::new(2, 5, "+x", "invalid argument")
+        );
     }
 
     #[test]
     fn test_solve_1() {
         let input = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
-        assert_eq!(solve_1(&parse_input(input)), 5);
+        assert_eq!(solve_1(&parse_input(input).unwrap()), 5);
     }
 
     #[test]
     fn test_solve_2() {
         let input = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
-        assert_eq!(solve_2(&parse_input(input)), 8);
+        assert_eq!(solve_2(&parse_input(input).unwrap()), Ok(8));
+        assert!(matches!(
+            solve_2(&parse_input("jmp +0\njmp -1").unwrap()),
+            Err(SolveError::NoSolution(_))
+        ));
+    }
+
+    #[test]
+    fn test_find_repairs() {
+        let input = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
+        assert_eq!(
+            find_repairs(&parse_input(input).unwrap()),
+            vec![Repair {
+                index: 7,
+                replacement: Instruction::Nop(-4),
+                acc: 8
+            }]
+        );
+        assert_eq!(
+            find_repairs(&parse_input("nop +2\njmp +0\nacc +1").unwrap()),
+            vec![
+                Repair {
+                    index: 0,
+                    replacement: Instruction::Jmp(2),
+                    acc: 1
+                },
+                Repair {
+                    index: 1,
+                    replacement: Instruction::Nop(0),
+                    acc: 1
+                }
+            ]
+        );
+        // jumping out of the program is not terminating
+        assert!(find_repairs(&parse_input("nop +5\njmp +0\njmp +0").unwrap()).is_empty());
+        // nothing to repair in a program which already terminates
+        assert!(find_repairs(&parse_input("nop +0\nacc +1").unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_disassembly() {
+        let input = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
+        let instructions = parse_input(input).unwrap();
+        let disassembly = Disassembly::new(&instructions);
+        assert_eq!(
+            disassembly
+                .blocks
+                .iter()
+                .map(|block| (block.start, block.exit))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, Exit::Block(1)),
+                (1, Exit::Block(4)),
+                (3, Exit::Block(1)),
+                (5, Exit::Block(4)),
+                (6, Exit::Block(2)),
+                (8, Exit::End)
+            ]
+        );
+        assert_eq!(
+            disassembly.reachable,
+            vec![true, true, true, false, true, false]
+        );
+        assert_eq!(disassembly.loops, vec![vec![1, 4, 2]]);
+        assert_eq!(disassembly.block_of(7), 4);
+        assert_eq!(
+            disassembly.to_string(),
+            "; 9 instructions (5 acc, 3 jmp, 1 nop), 7 reachable, 6 blocks, 1 loop(s)
+; loop 1 : L1 -> L4 -> L2
+L0:
+     0  nop +0
+L1: ; loop 1
+     1  acc +1
+     2  jmp +4   ; -> L4
+L2: ; loop 1
+     3  acc +3
+     4  jmp -3   ; -> L1
+L3: ; unreachable
+     5  acc -99
+L4: ; loop 1
+     6  acc +1
+     7  jmp -4   ; -> L2
+L5: ; unreachable
+     8  acc +6   ; -> end
+"
+        );
+    }
+
+    #[test]
+    fn test_dot() {
+        let instructions = parse_input("acc +1\njmp -1\njmp +5").unwrap();
+        assert_eq!(
+            Disassembly::new(&instructions).to_dot(),
+            "digraph program {
+    node [shape=box, fontname=\"monospace\"];
+    end [shape=doublecircle];
+    L0 [label=\"L0\\l0: acc +1\\l1: jmp -1\\l\"];
+    L1 [label=\"L1\\l2: jmp +5\\l\", style=dashed];
+    L0 -> L0 [color=red];
+    fault1 [shape=octagon, label=\"fault 7\"];
+    L1 -> fault1;
+}
+"
+        );
     }
 }
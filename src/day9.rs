@@ -1,10 +1,25 @@
+use crate::{
+    error::{parse_token, ParseError, SolveError},
+    solution::Solution,
+};
 use std::collections::HashSet;
 
-fn parse_input(input: &str) -> Vec<i64> {
-    input.lines().map(|s| s.parse().unwrap()).collect()
+pub fn parse_input(input: &str) -> Result<Vec<i64>, ParseError> {
+    input
+        .lines()
+        .map(|line| parse_token(input, line, "expected a number"))
+        .collect()
 }
 
-fn solve_1(numbers: &Vec<i64>, preamble_size: usize) -> i64 {
+/// The first number which is not the sum of two different numbers among the `preamble_size`
+/// before it.
+pub fn solve_1(numbers: &[i64], preamble_size: usize) -> Result<i64, SolveError> {
+    if numbers.len() <= preamble_size {
+        return Err(SolveError::NoSolution(format!(
+            "expected more than {} numbers",
+            preamble_size
+        )));
+    }
     let mut index = preamble_size;
 
     let mut set = HashSet::new();
@@ -15,60 +30,73 @@ fn solve_1(numbers: &Vec<i64>, preamble_size: usize) -> i64 {
     while index < numbers.len() {
         let mut found = false;
         for &available in &set {
-            let comp = numbers[index] - available;
-            if comp != available && set.contains(&comp) {
-                found = true;
-                break;
+            if let Some(comp) = numbers[index].checked_sub(available) {
+                if comp != available && set.contains(&comp) {
+                    found = true;
+                    break;
+                }
             }
         }
         if !found {
-            return numbers[index];
+            return Ok(numbers[index]);
         }
 
         set.remove(&numbers[index - preamble_size]);
         set.insert(numbers[index]);
         index += 1;
     }
-    panic!("no number found");
+    Err(SolveError::NoSolution(
+        "every number is the sum of two previous ones".to_owned(),
+    ))
 }
 
-fn solve_2(numbers: &Vec<i64>, invalid_number: i64) -> i64 {
+/// Sum of the smallest and largest numbers of a contiguous range of at least two numbers
+/// summing to `invalid_number`.
+pub fn solve_2(numbers: &[i64], invalid_number: i64) -> Result<i64, SolveError> {
+    let no_range = || SolveError::NoSolution(format!("no range sums to {}", invalid_number));
+    if numbers.len() < 2 {
+        return Err(no_range());
+    }
+    let target = invalid_number as i128;
     let mut start = 0;
     let mut end = 1;
-    let mut sum = numbers[start] + numbers[end];
-    while sum != invalid_number {
-        if sum > invalid_number {
-            sum -= numbers[start];
+    let mut sum = numbers[start] as i128 + numbers[end] as i128;
+    while sum != target {
+        if sum > target && end - start > 1 {
+            sum -= numbers[start] as i128;
             start += 1;
-            if start == end {
-                end += 1;
-                sum += numbers[end];
-            }
         } else {
             end += 1;
-            sum += numbers[end];
+            let number = numbers.get(end).ok_or_else(no_range)?;
+            sum += *number as i128;
         }
     }
     let min = numbers[start..=end].iter().min().unwrap();
     let max = numbers[start..=end].iter().max().unwrap();
-    min + max
+    min.checked_add(*max).ok_or_else(no_range)
 }
 
-pub fn run() {
-    let timer = std::time::Instant::now();
-    let input = std::fs::read_to_string("inputs/day9").unwrap();
-    let numbers = parse_input(&input);
-    let invalid_number = solve_1(&numbers, 25);
-    println!(
-        "day 9 solution 1 : {}, {}us",
-        invalid_number,
-        timer.elapsed().as_micros()
-    );
-    println!(
-        "day 9 solution 2 : {}, {}us",
-        solve_2(&numbers, invalid_number),
-        timer.elapsed().as_micros()
-    );
+pub struct Day9;
+
+impl Solution for Day9 {
+    type Input<'a> = Vec<i64>;
+    type Output1 = Result<i64, SolveError>;
+    type Output2 = Result<i64, SolveError>;
+
+    const DAY: u8 = 9;
+    const TITLE: &'static str = "Encoding Error";
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(numbers: &Self::Input<'_>) -> Result<i64, SolveError> {
+        solve_1(numbers, 25)
+    }
+
+    fn part_2(numbers: &Self::Input<'_>) -> Result<i64, SolveError> {
+        solve_2(numbers, solve_1(numbers, 25)?)
+    }
 }
 
 #[cfg(test)]
@@ -77,16 +105,33 @@ mod tests {
 
     fn get_numbers() -> Vec<i64> {
         let input = "35\n20\n15\n25\n47\n40\n62\n55\n65\n95\n102\n117\n150\n182\n127\n219\n299\n277\n309\n576";
-        parse_input(&input)
+        parse_input(input).unwrap()
     }
 
     #[test]
     fn test_solution_1() {
-        assert_eq!(solve_1(&get_numbers(), 5), 127);
+        assert_eq!(solve_1(&get_numbers(), 5), Ok(127));
     }
 
     #[test]
     fn test_solution_2() {
-        assert_eq!(solve_2(&get_numbers(), 127), 62);
+        assert_eq!(solve_2(&get_numbers(), 127), Ok(62));
+    }
+
+    #[test]
+    fn test_no_solution() {
+        let numbers = get_numbers();
+        assert!(matches!(
+            solve_1(&numbers[..8], 5),
+            Err(SolveError::NoSolution(_))
+        ));
+        assert!(matches!(
+            solve_1(&numbers, 25),
+            Err(SolveError::NoSolution(_))
+        ));
+        assert_eq!(
+            solve_2(&numbers, 1),
+            Err(SolveError::NoSolution("no range sums to 1".to_owned()))
+        );
     }
 }
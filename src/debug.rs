@@ -0,0 +1,268 @@
+use aoc2020::handheld::{InstructionSet, Machine, Watch};
+
+pub const HELP: &str = "commands:
+    step [N], s [N]     run the next N instructions (1 by default)
+    run, r              run until the program stops, a breakpoint or a watch
+    print, p            show the instruction pointer, the accumulator and the next instruction
+    list [N], l [N]     list the instructions around N (the instruction pointer by default)
+    break N, b N        add or remove a breakpoint on instruction N
+    watch acc [VALUE]   stop the runs when the accumulator changes, or reaches VALUE
+    unwatch             remove the watches
+    patch N INSTRUCTION replace instruction N, like `patch 7 nop -4`
+    trace [N]           show the last N instructions run (10 by default)
+    reset               restart the program, breakpoints, watches and patches are kept
+    quit, q             exit the debugger";
+
+/// Lines shown before and after the listed instruction.
+const LIST_CONTEXT: usize = 5;
+
+/// Result of a command which did not fail.
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    Continue(String),
+    Quit,
+}
+
+/// Interactive session over a program, commands are given one line at a time.
+pub struct Debugger<I: InstructionSet> {
+    machine: Machine<I>,
+}
+
+impl<I: InstructionSet> Debugger<I> {
+    pub fn new(mut machine: Machine<I>) -> Self {
+        machine.enable_trace();
+        Self { machine }
+    }
+
+    /// Runs a command, errors are messages to show to the user.
+    pub fn execute(&mut self, line: &str) -> Result<Outcome, String> {
+        let mut words = line.split_whitespace();
+        let output = match (words.next(), words.next()) {
+            (None, _) => String::new(),
+            (Some("step" | "s"), count) => {
+                let count = count.map_or(Ok(1), |count| parse_number(count, "count"))?;
+                self.step(count)
+            }
+            (Some("run" | "r"), None) => self.machine.run().to_string() + "\n" + &self.print(),
+            (Some("print" | "p"), None) => self.print(),
+            (Some("list" | "l"), index) => {
+                let index = match index {
+                    Some(index) => parse_number(index, "instruction")?,
+                    None => self
+                        .machine
+                        .current()
+                        .unwrap_or(self.machine.program().len()),
+                };
+                self.list(index)
+            }
+            (Some("break" | "b"), Some(index)) => {
+                let index = self.parse_index(index)?;
+                if self.machine.toggle_breakpoint(index) {
+                    format!("breakpoint set at {}", index)
+                } else {
+                    format!("breakpoint removed at {}", index)
+                }
+            }
+            (Some("watch"), Some("acc")) => {
+                let watch = match words.next() {
+                    Some(value) => Watch::AccEquals(
+                        value
+                            .parse()
+                            .map_err(|_| format!("invalid value `{}`", value))?,
+                    ),
+                    None => Watch::AccChanged,
+                };
+                self.machine.watch(watch);
+                format!("watching {}", watch)
+            }
+            (Some("unwatch"), None) => {
+                self.machine.clear_watches();
+                "watches removed".to_owned()
+            }
+            (Some("patch"), Some(index)) => {
+                let index = self.parse_index(index)?;
+                let source = words.collect::<Vec<_>>().join(" ");
+                let instruction = I::parse(&source).map_err(|error| {
+                    format!("invalid instruction `{}` : {}", source, error.message)
+                })?;
+                let previous = self.machine.patch(index, instruction.clone()).unwrap();
+                format!("{} : {} -> {}", index, previous, instruction)
+            }
+            (Some("trace"), count) => {
+                let count = count.map_or(Ok(10), |count| parse_number(count, "count"))?;
+                self.trace(count)
+            }
+            (Some("reset"), None) => {
+                self.machine.reset();
+                self.print()
+            }
+            (Some("quit" | "q"), None) => return Ok(Outcome::Quit),
+            (Some("help" | "h"), None) => HELP.to_owned(),
+            (Some(_), _) => {
+                return Err(format!(
+                    "invalid command `{}`, type help for the commands",
+                    line.trim()
+                ))
+            }
+        };
+        Ok(Outcome::Continue(output))
+    }
+
+    fn step(&mut self, count: usize) -> String {
+        let mut lines = vec![];
+        for _ in 0..count {
+            let ip = self.machine.current();
+            if let Err(stop) = self.machine.step() {
+                lines.push(stop.to_string());
+                break;
+            }
+            let ip = ip.unwrap();
+            lines.push(format!(
+                "{} : {}, acc {}",
+                ip,
+                self.machine.program()[ip],
+                self.machine.acc()
+            ));
+        }
+        lines.join("\n")
+    }
+
+    fn print(&self) -> String {
+        let next = match self.machine.current() {
+            Some(ip) => self.machine.program()[ip].to_string(),
+            None => "none".to_owned(),
+        };
+        format!(
+            "ip {}, acc {}, next {}",
+            self.machine.ip(),
+            self.machine.acc(),
+            next
+        )
+    }
+
+    /// Instructions around `index`, `>` marks the next one and `*` the breakpoints.
+    fn list(&self, index: usize) -> String {
+        let program = self.machine.program();
+        let breakpoints = self.machine.breakpoints().collect::<Vec<_>>();
+        let end = (index + LIST_CONTEXT + 1).min(program.len());
+        (index.saturating_sub(LIST_CONTEXT)..end)
+            .map(|line| {
+                format!(
+                    "{}{} {:>4} : {}",
+                    if self.machine.current() == Some(line) {
+                        '>'
+                    } else {
+                        ' '
+                    },
+                    if breakpoints.contains(&line) {
+                        '*'
+                    } else {
+                        ' '
+                    },
+                    line,
+                    program[line]
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn trace(&self, count: usize) -> String {
+        let trace = self.machine.trace();
+        trace[trace.len().saturating_sub(count)..]
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{} : {}, acc {}",
+                    entry.ip,
+                    self.machine.program()[entry.ip],
+                    entry.acc
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn parse_index(&self, value: &str) -> Result<usize, String> {
+        let index = parse_number(value, "instruction")?;
+        if index >= self.machine.program().len() {
+            return Err(format!(
+                "no instruction {}, the program has {}",
+                index,
+                self.machine.program().len()
+            ));
+        }
+        Ok(index)
+    }
+}
+
+fn parse_number(value: &str, name: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid {} `{}`", name, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc2020::day8::Instruction;
+
+    const PROGRAM: &str = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
+
+    fn output(debugger: &mut Debugger<Instruction>, line: &str) -> String {
+        match debugger.execute(line) {
+            Ok(Outcome::Continue(output)) => output,
+            other => panic!("unexpected outcome {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_step_and_run() {
+        let mut debugger = Debugger::new(Machine::load(PROGRAM).unwrap());
+        assert_eq!(
+            output(&mut debugger, "s 2"),
+            "0 : nop +0, acc 0\n1 : acc +1, acc 1"
+        );
+        assert_eq!(output(&mut debugger, "p"), "ip 2, acc 1, next jmp +4");
+        assert_eq!(output(&mut debugger, "b 3"), "breakpoint set at 3");
+        assert_eq!(
+            output(&mut debugger, "run"),
+            "breakpoint at 3\nip 3, acc 2, next acc +3"
+        );
+        assert_eq!(
+            output(&mut debugger, "trace 2"),
+            "6 : acc +1, acc 2\n7 : jmp -4, acc 2"
+        );
+        assert_eq!(
+            output(&mut debugger, "r"),
+            "loop, instruction 1 would run again\nip 1, acc 5, next acc +1"
+        );
+        assert_eq!(debugger.execute("quit"), Ok(Outcome::Quit));
+    }
+
+    #[test]
+    fn test_patch_and_watch() {
+        let mut debugger = Debugger::new(Machine::load(PROGRAM).unwrap());
+        assert_eq!(
+            output(&mut debugger, "patch 7 nop -4"),
+            "7 : jmp -4 -> nop -4"
+        );
+        assert_eq!(output(&mut debugger, "watch acc 2"), "watching acc == 2");
+        assert_eq!(
+            output(&mut debugger, "run"),
+            "watch acc == 2 after instruction 6\nip 7, acc 2, next nop -4"
+        );
+        output(&mut debugger, "unwatch");
+        assert_eq!(
+            output(&mut debugger, "run"),
+            "halted\nip 9, acc 8, next none"
+        );
+        assert_eq!(
+            output(&mut debugger, "list 8"),
+            "      3 : acc +3\n      4 : jmp -3\n      5 : acc -99\n      6 : acc +1\n      7 : nop -4\n      8 : acc +6"
+        );
+        assert!(debugger.execute("patch 9 nop +0").is_err());
+        assert!(debugger.execute("patch 1 mul +2").is_err());
+        assert!(debugger.execute("frobnicate").is_err());
+    }
+}
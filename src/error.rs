@@ -0,0 +1,175 @@
+use std::{fmt::Display, str::FromStr};
+
+/// Error returned by the parsers, locating the offending token in the input.
+///
+/// Lines and columns start at 1, columns are counted in characters.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub token: String,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, token: &str, message: &str) -> Self {
+        Self {
+            line,
+            column,
+            token: token.to_owned(),
+            message: message.to_owned(),
+        }
+    }
+
+    /// Builds an error on `token`, which must be a slice of `input` : its location is computed
+    /// from its position in `input`. Use an empty slice to point after the last token.
+    pub fn at(input: &str, token: &str, message: &str) -> Self {
+        let (line, column) = locate(input, token);
+        Self::new(line, column, token, message)
+    }
+
+    /// Moves an error located in `part`, a slice of `input`, so it is located in `input`.
+    pub fn within(mut self, input: &str, part: &str) -> Self {
+        let (line, column) = locate(input, part);
+        if self.line == 1 {
+            self.column += column - 1;
+        }
+        self.line += line - 1;
+        self
+    }
+}
+
+/// Line and column of `token` in `input`, `token` is expected to be a slice of `input`.
+fn locate(input: &str, token: &str) -> (usize, usize) {
+    let start = input.as_ptr() as usize;
+    let offset = (token.as_ptr() as usize)
+        .saturating_sub(start)
+        .min(input.len());
+    debug_assert!(
+        token.as_ptr() as usize >= start,
+        "token is not part of the input"
+    );
+    let before = &input[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.token.is_empty() {
+            write!(
+                f,
+                "line {}, column {} : {}",
+                self.line, self.column, self.message
+            )
+        } else {
+            write!(
+                f,
+                "line {}, column {} : {}, found `{}`",
+                self.line, self.column, self.message, self.token
+            )
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Error returned by the solvers which could not find an answer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SolveError {
+    /// The search was exhausted, the message tells what could not be found.
+    NoSolution(String),
+    /// The solver stopped at a checkpoint after its cancellation flag was raised.
+    Cancelled,
+    /// The time budget of the day was exceeded before the part was solved.
+    TimedOut,
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolveError::NoSolution(message) => write!(f, "no solution, {}", message),
+            SolveError::Cancelled => write!(f, "cancelled"),
+            SolveError::TimedOut => write!(f, "timed out"),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+/// Parses `token`, a slice of `input`, reporting `message` at its location on failure.
+pub fn parse_token<T: FromStr>(input: &str, token: &str, message: &str) -> Result<T, ParseError> {
+    token
+        .parse()
+        .map_err(|_| ParseError::at(input, token, message))
+}
+
+/// Returns the next token of a split of `input`, or an error pointing at the end of `input`.
+pub fn next_token<'a, I: Iterator<Item = &'a str>>(
+    tokens: &mut I,
+    input: &'a str,
+    message: &str,
+) -> Result<&'a str, ParseError> {
+    tokens
+        .next()
+        .ok_or_else(|| ParseError::at(input, &input[input.len()..], message))
+}
+
+/// Checks every character of `line`, a slice of `input`, is one of `allowed`.
+pub fn expect_chars(input: &str, line: &str, allowed: &str) -> Result<(), ParseError> {
+    match line.char_indices().find(|&(_, c)| !allowed.contains(c)) {
+        Some((index, c)) => Err(ParseError::at(
+            input,
+            &line[index..index + c.len_utf8()],
+            &format!("expected one of `{}`", allowed),
+        )),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_at() {
+        let input = "acc +1\nfoo +3\njmp -2";
+        let token = &input[7..10];
+        assert_eq!(
+            ParseError::at(input, token, "invalid operation"),
+            ParseError::new(2, 1, "foo", "invalid operation")
+        );
+        assert_eq!(ParseError::at(input, &input[input.len()..], "").column, 7);
+    }
+
+    #[test]
+    fn test_within() {
+        let input = "a\nbb ccc\nd";
+        let line = &input[2..8];
+        let error = ParseError::at(line, &line[3..], "invalid");
+        assert_eq!(
+            error.within(input, line),
+            ParseError::at(input, &input[5..8], "invalid")
+        );
+    }
+
+    #[test]
+    fn test_expect_chars() {
+        let input = "..#\n.x#";
+        assert!(expect_chars(input, &input[..3], ".#").is_ok());
+        assert_eq!(
+            expect_chars(input, &input[4..], ".#"),
+            Err(ParseError::new(2, 2, "x", "expected one of `.#`"))
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let error = ParseError::new(2, 5, "x", "expected a number");
+        assert_eq!(
+            error.to_string(),
+            "line 2, column 5 : expected a number, found `x`"
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use aoc2020::{error::SolveError, runner::DayResult, solution::Answer};
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl Format {
+    pub fn from_arg(value: &str) -> Option<Self> {
+        match value {
+            "text" => Some(Format::Text),
+            "json" => Some(Format::Json),
+            "csv" => Some(Format::Csv),
+            _ => None,
+        }
+    }
+}
+
+const CSV_HEADER: &str = "day,part,answer,type,parse_us,solve_us";
+
+/// Prints the results of the days as they are solved.
+///
+/// Errors are not part of the report, they are printed on stderr by the caller.
+pub struct Report {
+    format: Format,
+    count: usize,
+    /// Time spent on each day, in the order they were reported.
+    times: Vec<(u8, Duration)>,
+}
+
+impl Report {
+    pub fn new(format: Format) -> Self {
+        match format {
+            Format::Text => {}
+            Format::Json => println!("{{\"results\":["),
+            Format::Csv => println!("{}", CSV_HEADER),
+        }
+        Self {
+            format,
+            count: 0,
+            times: vec![],
+        }
+    }
+
+    pub fn day(&mut self, result: &DayResult) {
+        for line in format_day(self.format, result) {
+            if self.format == Format::Json && self.count > 0 {
+                println!(",");
+            }
+            print!("{}", line);
+            if self.format != Format::Json {
+                println!();
+            }
+            self.count += 1;
+        }
+        self.times.push((result.day, result.total_time()));
+    }
+
+    /// The text format can end with the time of every day, useful when they ran in parallel and
+    /// the total is less than their sum.
+    pub fn finish(self, total: Duration, breakdown: bool) {
+        match self.format {
+            Format::Text if breakdown => {
+                for (day, time) in &self.times {
+                    println!("day {} : {}us", day, time.as_micros());
+                }
+                let sum = self.times.iter().map(|(_, time)| *time).sum::<Duration>();
+                println!(
+                    "{}us ({}us summed over days)",
+                    total.as_micros(),
+                    sum.as_micros()
+                );
+            }
+            Format::Text => println!("{}us", total.as_micros()),
+            Format::Json => {
+                if self.count > 0 {
+                    println!();
+                }
+                println!("],\"total_us\":{}}}", total.as_micros());
+            }
+            Format::Csv => {}
+        }
+    }
+}
+
+/// One line per part : the text format reports the time elapsed since the start of the day.
+fn format_day(format: Format, result: &DayResult) -> Vec<String> {
+    let parse_us = result.parse_time.as_micros();
+    let mut elapsed = result.parse_time;
+    result
+        .parts
+        .iter()
+        .map(|part| {
+            elapsed += part.solve_time;
+            let solve_us = part.solve_time.as_micros();
+            match format {
+                Format::Text => format!(
+                    "day {} solution {} : {}, {}us",
+                    result.day,
+                    part.part,
+                    answer_text(&part.answer),
+                    elapsed.as_micros()
+                ),
+                Format::Json => format!(
+                    "{{\"day\":{},\"part\":{},\"answer\":{},\"type\":\"{}\",{}\"parse_us\":{},\"solve_us\":{}}}",
+                    result.day,
+                    part.part,
+                    part.answer.as_ref().map_or("null".to_owned(), json_answer),
+                    answer_kind(&part.answer),
+                    match &part.answer {
+                        Ok(_) => String::new(),
+                        Err(error) => format!("\"error\":{},", json_string(&error.to_string())),
+                    },
+                    parse_us,
+                    solve_us
+                ),
+                Format::Csv => format!(
+                    "{},{},{},{},{},{}",
+                    result.day,
+                    part.part,
+                    csv_field(&answer_text(&part.answer)),
+                    answer_kind(&part.answer),
+                    parse_us,
+                    solve_us
+                ),
+            }
+        })
+        .collect()
+}
+
+/// Failed parts show the error in place of the answer.
+fn answer_text(answer: &Result<Answer, SolveError>) -> String {
+    match answer {
+        Ok(answer) => answer.to_string(),
+        Err(error) => error.to_string(),
+    }
+}
+
+fn answer_kind(answer: &Result<Answer, SolveError>) -> &'static str {
+    answer.as_ref().map_or("error", Answer::kind)
+}
+
+fn json_answer(answer: &Answer) -> String {
+    match answer {
+        Answer::Integer(value) => value.to_string(),
+        Answer::Text(value) => json_string(value),
+        Answer::None => "null".to_owned(),
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut result = String::with_capacity(value.len() + 2);
+    result.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            c if c.is_control() => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// Quotes the field when it contains a separator, a quote or a line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc2020::runner::PartResult;
+
+    fn result() -> DayResult {
+        DayResult {
+            day: 21,
+            parse_time: Duration::from_micros(10),
+            parts: vec![
+                PartResult {
+                    part: 1,
+                    answer: Ok(Answer::Integer(5)),
+                    solve_time: Duration::from_micros(20),
+                },
+                PartResult {
+                    part: 2,
+                    answer: Ok(Answer::Text("mxmxvkd,sqjhc".to_owned())),
+                    solve_time: Duration::from_micros(30),
+                },
+            ],
+        }
+    }
+
+    fn failed() -> DayResult {
+        DayResult {
+            day: 25,
+            parse_time: Duration::from_micros(10),
+            parts: vec![PartResult {
+                part: 1,
+                answer: Err(SolveError::TimedOut),
+                solve_time: Duration::from_micros(20),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_format_text() {
+        assert_eq!(
+            format_day(Format::Text, &result()),
+            vec![
+                "day 21 solution 1 : 5, 30us",
+                "day 21 solution 2 : mxmxvkd,sqjhc, 60us"
+            ]
+        );
+        assert_eq!(
+            format_day(Format::Text, &failed()),
+            vec!["day 25 solution 1 : timed out, 30us"]
+        );
+    }
+
+    #[test]
+    fn test_format_json() {
+        assert_eq!(
+            format_day(Format::Json, &result()),
+            vec![
+                "{\"day\":21,\"part\":1,\"answer\":5,\"type\":\"integer\",\"parse_us\":10,\"solve_us\":20}",
+                "{\"day\":21,\"part\":2,\"answer\":\"mxmxvkd,sqjhc\",\"type\":\"text\",\"parse_us\":10,\"solve_us\":30}"
+            ]
+        );
+        assert_eq!(
+            format_day(Format::Json, &failed()),
+            vec!["{\"day\":25,\"part\":1,\"answer\":null,\"type\":\"error\",\"error\":\"timed out\",\"parse_us\":10,\"solve_us\":20}"]
+        );
+        assert_eq!(json_string("a\"b\\c\n"), "\"a\\\"b\\\\c\\n\"");
+    }
+
+    #[test]
+    fn test_format_csv() {
+        assert_eq!(
+            format_day(Format::Csv, &result()),
+            vec!["21,1,5,integer,10,20", "21,2,\"mxmxvkd,sqjhc\",text,10,30"]
+        );
+        assert_eq!(
+            format_day(Format::Csv, &failed()),
+            vec!["25,1,timed out,error,10,20"]
+        );
+        assert_eq!(csv_field("a\"b"), "\"a\"\"b\"");
+    }
+}
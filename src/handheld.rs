@@ -0,0 +1,302 @@
+use crate::error::ParseError;
+use std::{collections::HashSet, convert::TryFrom, fmt::Display};
+
+/// Registers of the handheld console : the accumulator and the index of the next instruction.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Registers {
+    pub acc: i64,
+    pub ip: i64,
+}
+
+/// The instruction set run by a `Machine`, every instruction moves the instruction pointer itself.
+pub trait InstructionSet: Clone + Display {
+    /// Parses a single instruction, `line` being the whole line.
+    fn parse(line: &str) -> Result<Self, ParseError>;
+    fn execute(&self, registers: &mut Registers);
+}
+
+/// Condition stopping `Machine::run` once it holds after an instruction.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Watch {
+    /// The accumulator changed.
+    AccChanged,
+    /// The accumulator has the given value.
+    AccEquals(i64),
+}
+
+impl Watch {
+    fn triggered(&self, before: &Registers, after: &Registers) -> bool {
+        match *self {
+            Watch::AccChanged => before.acc != after.acc,
+            Watch::AccEquals(value) => after.acc == value,
+        }
+    }
+}
+
+/// Why the machine stopped.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Stop {
+    /// The instruction pointer is right after the last instruction.
+    Halted,
+    /// The instruction pointer left the program.
+    Fault(i64),
+    /// The instruction at this index would run a second time.
+    Loop(usize),
+    /// The instruction at this index has a breakpoint and was not run.
+    Breakpoint(usize),
+    /// The watch held after the instruction at this index.
+    Watch(Watch, usize),
+}
+
+impl Display for Watch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Watch::AccChanged => write!(f, "acc changed"),
+            Watch::AccEquals(value) => write!(f, "acc == {}", value),
+        }
+    }
+}
+
+impl Display for Stop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Stop::Halted => write!(f, "halted"),
+            Stop::Fault(ip) => write!(f, "fault, jumped to {}", ip),
+            Stop::Loop(ip) => write!(f, "loop, instruction {} would run again", ip),
+            Stop::Breakpoint(ip) => write!(f, "breakpoint at {}", ip),
+            Stop::Watch(watch, ip) => write!(f, "watch {} after instruction {}", watch, ip),
+        }
+    }
+}
+
+/// Instruction run along with the accumulator after it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TraceEntry {
+    pub ip: usize,
+    pub acc: i64,
+}
+
+/// A program loaded in the handheld console.
+///
+/// The programs never run an instruction twice when they terminate, `run` stops on the first
+/// instruction about to run again since the last `reset`.
+#[derive(Clone, Debug)]
+pub struct Machine<I: InstructionSet> {
+    program: Vec<I>,
+    registers: Registers,
+    visited: HashSet<usize>,
+    breakpoints: HashSet<usize>,
+    watches: Vec<Watch>,
+    trace: Option<Vec<TraceEntry>>,
+}
+
+impl<I: InstructionSet> Machine<I> {
+    pub fn new(program: Vec<I>) -> Self {
+        Self {
+            program,
+            registers: Registers::default(),
+            visited: HashSet::new(),
+            breakpoints: HashSet::new(),
+            watches: vec![],
+            trace: None,
+        }
+    }
+
+    /// Parses one instruction per line.
+    pub fn load(input: &str) -> Result<Self, ParseError> {
+        let program = input
+            .lines()
+            .map(|line| I::parse(line).map_err(|error| error.within(input, line)))
+            .collect::<Result<_, _>>()?;
+        Ok(Self::new(program))
+    }
+
+    pub fn program(&self) -> &[I] {
+        &self.program
+    }
+
+    pub fn registers(&self) -> Registers {
+        self.registers
+    }
+
+    pub fn acc(&self) -> i64 {
+        self.registers.acc
+    }
+
+    pub fn ip(&self) -> i64 {
+        self.registers.ip
+    }
+
+    /// Index of the next instruction when it is in the program.
+    pub fn current(&self) -> Option<usize> {
+        usize::try_from(self.registers.ip)
+            .ok()
+            .filter(|&ip| ip < self.program.len())
+    }
+
+    /// Back to the first instruction with an empty accumulator, breakpoints and watches are kept
+    /// and the trace is cleared.
+    pub fn reset(&mut self) {
+        self.registers = Registers::default();
+        self.visited.clear();
+        if let Some(trace) = &mut self.trace {
+            trace.clear();
+        }
+    }
+
+    /// Replaces the instruction at `index`, returns the previous one or `None` if the index is
+    /// out of the program.
+    pub fn patch(&mut self, index: usize, instruction: I) -> Option<I> {
+        self.program
+            .get_mut(index)
+            .map(|current| std::mem::replace(current, instruction))
+    }
+
+    /// Adds a breakpoint, or removes it if there was already one, returns whether it is set.
+    pub fn toggle_breakpoint(&mut self, index: usize) -> bool {
+        if self.breakpoints.remove(&index) {
+            false
+        } else {
+            self.breakpoints.insert(index);
+            true
+        }
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = usize> + '_ {
+        self.breakpoints.iter().copied()
+    }
+
+    pub fn watch(&mut self, watch: Watch) {
+        if !self.watches.contains(&watch) {
+            self.watches.push(watch);
+        }
+    }
+
+    pub fn clear_watches(&mut self) {
+        self.watches.clear();
+    }
+
+    /// Starts recording the instructions run, the previous trace is dropped.
+    pub fn enable_trace(&mut self) {
+        self.trace = Some(vec![]);
+    }
+
+    pub fn trace(&self) -> &[TraceEntry] {
+        self.trace.as_deref().unwrap_or_default()
+    }
+
+    /// Why the machine cannot run the next instruction, if it cannot.
+    fn stopped(&self) -> Option<Stop> {
+        match self.current() {
+            Some(ip) if self.visited.contains(&ip) => Some(Stop::Loop(ip)),
+            Some(_) => None,
+            None if self.registers.ip == self.program.len() as i64 => Some(Stop::Halted),
+            None => Some(Stop::Fault(self.registers.ip)),
+        }
+    }
+
+    /// Runs the next instruction, ignoring breakpoints and watches, unless the machine halted,
+    /// faulted or would loop.
+    pub fn step(&mut self) -> Result<(), Stop> {
+        if let Some(stop) = self.stopped() {
+            return Err(stop);
+        }
+        let ip = self.registers.ip as usize;
+        self.visited.insert(ip);
+        self.program[ip].execute(&mut self.registers);
+        if let Some(trace) = &mut self.trace {
+            trace.push(TraceEntry {
+                ip,
+                acc: self.registers.acc,
+            });
+        }
+        Ok(())
+    }
+
+    /// Runs instructions until the machine stops, a breakpoint on the first instruction is
+    /// ignored so the run can resume from it.
+    pub fn run(&mut self) -> Stop {
+        let mut first = true;
+        loop {
+            if let Some(stop) = self.stopped() {
+                return stop;
+            }
+            let ip = self.registers.ip as usize;
+            if !first && self.breakpoints.contains(&ip) {
+                return Stop::Breakpoint(ip);
+            }
+            first = false;
+
+            let before = self.registers;
+            self.step().unwrap();
+            if let Some(&watch) = self
+                .watches
+                .iter()
+                .find(|watch| watch.triggered(&before, &self.registers))
+            {
+                return Stop::Watch(watch, ip);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day8::Instruction;
+
+    const PROGRAM: &str = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
+
+    #[test]
+    fn test_run() {
+        let mut machine = Machine::<Instruction>::load(PROGRAM).unwrap();
+        assert_eq!(machine.run(), Stop::Loop(1));
+        assert_eq!(machine.acc(), 5);
+
+        machine.patch(7, Instruction::Nop(-4));
+        machine.reset();
+        assert_eq!(machine.run(), Stop::Halted);
+        assert_eq!(machine.acc(), 8);
+        assert_eq!(machine.step(), Err(Stop::Halted));
+
+        machine.patch(8, Instruction::Jmp(-10));
+        machine.reset();
+        assert_eq!(machine.run(), Stop::Fault(-2));
+    }
+
+    #[test]
+    fn test_breakpoints_and_watches() {
+        let mut machine = Machine::<Instruction>::load(PROGRAM).unwrap();
+        assert!(machine.toggle_breakpoint(3));
+        assert_eq!(machine.run(), Stop::Breakpoint(3));
+        assert_eq!((machine.ip(), machine.acc()), (3, 2));
+        // resumes from the breakpoint
+        assert_eq!(machine.run(), Stop::Loop(1));
+        assert!(!machine.toggle_breakpoint(3));
+
+        machine.reset();
+        machine.watch(Watch::AccChanged);
+        assert_eq!(machine.run(), Stop::Watch(Watch::AccChanged, 1));
+        machine.clear_watches();
+        machine.watch(Watch::AccEquals(5));
+        assert_eq!(machine.run(), Stop::Watch(Watch::AccEquals(5), 3));
+    }
+
+    #[test]
+    fn test_trace() {
+        let mut machine = Machine::<Instruction>::load(PROGRAM).unwrap();
+        machine.enable_trace();
+        machine.step().unwrap();
+        machine.step().unwrap();
+        machine.step().unwrap();
+        assert_eq!(
+            machine.trace(),
+            &[
+                TraceEntry { ip: 0, acc: 0 },
+                TraceEntry { ip: 1, acc: 1 },
+                TraceEntry { ip: 2, acc: 1 }
+            ]
+        );
+        assert_eq!(machine.current(), Some(6));
+    }
+}
@@ -0,0 +1,121 @@
+use std::{
+    fmt::Display,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+/// Environment variable overriding the default inputs directory.
+pub const INPUTS_DIR_VAR: &str = "AOC2020_INPUTS";
+
+#[derive(Debug, PartialEq)]
+pub enum InputSource {
+    /// `dayN` files in the default inputs directory
+    Default,
+    /// `dayN` files in the given directory
+    Directory(PathBuf),
+    /// a single file, used as is whatever the day
+    File(PathBuf),
+    Stdin,
+}
+
+impl InputSource {
+    /// `-` reads from stdin, an existing directory is searched for `dayN` files and anything
+    /// else is read as a file.
+    pub fn from_arg(arg: &str) -> Self {
+        if arg == "-" {
+            InputSource::Stdin
+        } else if Path::new(arg).is_dir() {
+            InputSource::Directory(PathBuf::from(arg))
+        } else {
+            InputSource::File(PathBuf::from(arg))
+        }
+    }
+
+    /// Whether the source can only provide the input of a single day.
+    pub fn is_single(&self) -> bool {
+        matches!(self, InputSource::File(_) | InputSource::Stdin)
+    }
+
+    /// Reads the input of the given day, trailing whitespace is removed so inputs saved with a
+    /// final newline or piped through `echo` behave like the stored ones.
+    pub fn read(&self, day: u8) -> Result<String, InputError> {
+        let contents = match self {
+            InputSource::Default => read_file(&default_directory().join(format!("day{}", day))),
+            InputSource::Directory(directory) => read_file(&directory.join(format!("day{}", day))),
+            InputSource::File(path) => read_file(path),
+            InputSource::Stdin => {
+                let mut contents = String::new();
+                std::io::stdin()
+                    .read_to_string(&mut contents)
+                    .map_err(InputError::Stdin)?;
+                Ok(contents)
+            }
+        }?;
+        Ok(contents.trim_end().to_owned())
+    }
+}
+
+/// The directory given by `AOC2020_INPUTS` if set, the `inputs` directory of the crate otherwise
+/// so the binary does not depend on the current working directory.
+pub fn default_directory() -> PathBuf {
+    match std::env::var_os(INPUTS_DIR_VAR) {
+        Some(directory) => PathBuf::from(directory),
+        None => Path::new(env!("CARGO_MANIFEST_DIR")).join("inputs"),
+    }
+}
+
+fn read_file(path: &Path) -> Result<String, InputError> {
+    std::fs::read_to_string(path).map_err(|error| match error.kind() {
+        std::io::ErrorKind::NotFound => InputError::NotFound(path.to_owned()),
+        _ => InputError::Io(path.to_owned(), error),
+    })
+}
+
+#[derive(Debug)]
+pub enum InputError {
+    NotFound(PathBuf),
+    Io(PathBuf, std::io::Error),
+    Stdin(std::io::Error),
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputError::NotFound(path) => write!(f, "input file {} not found", path.display()),
+            InputError::Io(path, error) => write!(f, "cannot read {} : {}", path.display(), error),
+            InputError::Stdin(error) => write!(f, "cannot read stdin : {}", error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_arg() {
+        assert_eq!(InputSource::from_arg("-"), InputSource::Stdin);
+        assert_eq!(
+            InputSource::from_arg(env!("CARGO_MANIFEST_DIR")),
+            InputSource::Directory(PathBuf::from(env!("CARGO_MANIFEST_DIR")))
+        );
+        assert_eq!(
+            InputSource::from_arg("other/day7.txt"),
+            InputSource::File(PathBuf::from("other/day7.txt"))
+        );
+    }
+
+    #[test]
+    fn test_read_missing_file() {
+        let source = InputSource::Directory(PathBuf::from("does/not/exist"));
+        match source.read(7) {
+            Err(InputError::NotFound(path)) => assert_eq!(path, Path::new("does/not/exist/day7")),
+            _ => panic!("expected a not found error"),
+        }
+    }
+
+    #[test]
+    fn test_read_default() {
+        assert!(InputSource::Default.read(1).is_ok());
+    }
+}
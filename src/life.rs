@@ -0,0 +1,315 @@
+use crate::error::ParseError;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+/// Life-like rule : the neighbour counts for which a dead cell is born or a live cell survives.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rule {
+    born: Vec<usize>,
+    survive: Vec<usize>,
+}
+
+impl Rule {
+    /// Parses a rule like `B3/S23`, the counts are separated by commas when some of them have
+    /// several digits like in `B3/S2,3,10`.
+    ///
+    /// A cell cannot be born without neighbours, the infinite dead space would come to life.
+    pub fn new(input: &str) -> Result<Self, ParseError> {
+        let mut born = None;
+        let mut survive = None;
+        for part in input.split('/') {
+            let (counts, kind) = match part.chars().next() {
+                Some('B' | 'b') => (&mut born, "B"),
+                Some('S' | 's') => (&mut survive, "S"),
+                _ => {
+                    return Err(ParseError::at(
+                        input,
+                        part,
+                        "expected `B` or `S` followed by neighbour counts",
+                    ))
+                }
+            };
+            if counts.is_some() {
+                return Err(ParseError::at(
+                    input,
+                    &part[..1],
+                    &format!("`{}` is given twice", kind),
+                ));
+            }
+            let parsed = parse_counts(input, &part[1..])?;
+            if kind == "B" && parsed.contains(&0) {
+                return Err(ParseError::at(
+                    input,
+                    part,
+                    "cells cannot be born without neighbours",
+                ));
+            }
+            *counts = Some(parsed);
+        }
+        let (born, survive) = match (born, survive) {
+            (Some(born), Some(survive)) => (born, survive),
+            _ => {
+                return Err(ParseError::at(
+                    input,
+                    &input[input.len()..],
+                    "expected a rule like `B3/S23`",
+                ))
+            }
+        };
+        Ok(Self { born, survive })
+    }
+
+    /// The game of life, `B3/S23`.
+    pub fn conway() -> Self {
+        Self {
+            born: vec![3],
+            survive: vec![2, 3],
+        }
+    }
+
+    /// Whether the cell is alive in the next generation.
+    pub fn next(&self, alive: bool, neighbours: usize) -> bool {
+        if alive {
+            self.survive.contains(&neighbours)
+        } else {
+            self.born.contains(&neighbours)
+        }
+    }
+}
+
+/// Single digits, or numbers separated by commas, sorted and without duplicates.
+fn parse_counts(input: &str, counts: &str) -> Result<Vec<usize>, ParseError> {
+    let mut result = if counts.contains(',') {
+        counts
+            .split(',')
+            .map(|count| {
+                count
+                    .parse()
+                    .map_err(|_| ParseError::at(input, count, "expected a neighbour count"))
+            })
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        counts
+            .char_indices()
+            .map(|(index, c)| {
+                c.to_digit(10).map(|count| count as usize).ok_or_else(|| {
+                    ParseError::at(
+                        input,
+                        &counts[index..index + c.len_utf8()],
+                        "expected a neighbour count",
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    result.sort_unstable();
+    result.dedup();
+    Ok(result)
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let counts = |counts: &[usize]| {
+            let separator = if counts.iter().any(|&count| count > 9) {
+                ","
+            } else {
+                ""
+            };
+            counts
+                .iter()
+                .map(usize::to_string)
+                .collect::<Vec<_>>()
+                .join(separator)
+        };
+        write!(f, "B{}/S{}", counts(&self.born), counts(&self.survive))
+    }
+}
+
+/// Sparse cellular automaton on an infinite grid of any dimension, only the live cells are
+/// stored.
+#[derive(Clone, Debug)]
+pub struct Life {
+    rule: Rule,
+    dimension: usize,
+    /// Moves to the `3^dimension - 1` neighbours of a cell.
+    offsets: Vec<Vec<i32>>,
+    cells: HashSet<Vec<i32>>,
+}
+
+impl Life {
+    pub fn new(rule: Rule, dimension: usize) -> Self {
+        assert!(dimension > 0, "the grid needs at least one dimension");
+        let mut offsets = vec![vec![]];
+        for _ in 0..dimension {
+            offsets = offsets
+                .into_iter()
+                .flat_map(|offset: Vec<i32>| {
+                    (-1..=1).map(move |step| {
+                        let mut offset = offset.clone();
+                        offset.push(step);
+                        offset
+                    })
+                })
+                .collect();
+        }
+        offsets.retain(|offset| offset.iter().any(|&step| step != 0));
+        Self {
+            rule,
+            dimension,
+            offsets,
+            cells: HashSet::new(),
+        }
+    }
+
+    /// Live cells from a grid of `#` on the first two dimensions, the others being 0.
+    pub fn from_grid(input: &str, rule: Rule, dimension: usize) -> Self {
+        assert!(dimension >= 2, "a grid needs two dimensions");
+        let mut life = Self::new(rule, dimension);
+        for (row, line) in input.lines().enumerate() {
+            for (column, c) in line.chars().enumerate() {
+                if c == '#' {
+                    let mut cell = vec![0; dimension];
+                    cell[0] = row as i32;
+                    cell[1] = column as i32;
+                    life.insert(cell);
+                }
+            }
+        }
+        life
+    }
+
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    pub fn insert(&mut self, cell: Vec<i32>) {
+        assert_eq!(
+            cell.len(),
+            self.dimension,
+            "the cell has the wrong dimension"
+        );
+        self.cells.insert(cell);
+    }
+
+    pub fn is_alive(&self, cell: &[i32]) -> bool {
+        self.cells.contains(cell)
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = &[i32]> {
+        self.cells.iter().map(Vec::as_slice)
+    }
+
+    /// Number of live cells.
+    pub fn population(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Computes the next generation, only the neighbours of live cells can change.
+    pub fn step(&mut self) {
+        let mut neighbours = HashMap::<Vec<i32>, usize>::new();
+        for cell in &self.cells {
+            for offset in &self.offsets {
+                let neighbour = cell.iter().zip(offset).map(|(x, step)| x + step).collect();
+                *neighbours.entry(neighbour).or_default() += 1;
+            }
+        }
+        let mut cells = HashSet::new();
+        // the live cells without live neighbours are not counted
+        if self.rule.next(true, 0) {
+            cells.extend(
+                self.cells
+                    .iter()
+                    .filter(|&cell| !neighbours.contains_key(cell))
+                    .cloned(),
+            );
+        }
+        for (cell, count) in neighbours {
+            if self.rule.next(self.cells.contains(&cell), count) {
+                cells.insert(cell);
+            }
+        }
+        self.cells = cells;
+    }
+
+    pub fn run(&mut self, generations: usize) {
+        for _ in 0..generations {
+            self.step();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule() {
+        assert_eq!(Rule::new("B3/S23"), Ok(Rule::conway()));
+        assert_eq!(Rule::new("s32/b3"), Ok(Rule::conway()));
+        let rule = Rule::new("B36/S").unwrap();
+        assert_eq!(rule.to_string(), "B36/S");
+        assert!(rule.next(false, 6) && !rule.next(true, 3));
+        let rule = Rule::new("B3,12/S2,3").unwrap();
+        assert!(rule.next(false, 12));
+        assert_eq!(rule.to_string(), "B3,12/S23");
+
+        assert_eq!(
+            Rule::new("B3/X23").err().unwrap(),
+            ParseError::new(
+                1,
+                4,
+                "X23",
+                "expected `B` or `S` followed by neighbour counts"
+            )
+        );
+        assert_eq!(
+            Rule::new("B3/S2a").err().unwrap(),
+            ParseError::new(1, 6, "a", "expected a neighbour count")
+        );
+        assert_eq!(
+            Rule::new("B3/B2").err().unwrap(),
+            ParseError::new(1, 4, "B", "`B` is given twice")
+        );
+        assert!(Rule::new("B3").is_err());
+        assert!(Rule::new("B03/S23").is_err());
+    }
+
+    #[test]
+    fn test_blinker() {
+        let mut life = Life::from_grid(".#.\n.#.\n.#.", Rule::conway(), 2);
+        life.step();
+        let mut cells = life.cells().map(<[i32]>::to_vec).collect::<Vec<_>>();
+        cells.sort_unstable();
+        assert_eq!(cells, [[1, 0], [1, 1], [1, 2]]);
+        life.step();
+        assert!(life.is_alive(&[0, 1]) && life.is_alive(&[2, 1]));
+        assert_eq!(life.population(), 3);
+    }
+
+    #[test]
+    fn test_dimensions() {
+        // the middle of a line survives, the cells next to the 3 of them are born
+        for dimension in [2, 6, 7] {
+            let mut life = Life::new(Rule::conway(), dimension);
+            for x in -1..=1 {
+                let mut cell = vec![0; dimension];
+                cell[0] = x;
+                life.insert(cell);
+            }
+            life.step();
+            assert_eq!(life.population(), 3usize.pow(dimension as u32 - 1));
+        }
+
+        // a lone cell survives when no neighbour is required
+        let mut life = Life::new(Rule::new("B1/S0").unwrap(), 1);
+        life.insert(vec![0]);
+        life.step();
+        assert_eq!(life.population(), 3);
+    }
+}
@@ -1,55 +1,426 @@
-mod day1;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day2;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
+mod cli;
+mod debug;
+mod format;
+
+use aoc2020::{
+    answers::{self, Answers, Verdict},
+    bench::{self, Baseline, BenchResult},
+    day20::{self, RenderOptions},
+    day8,
+    error::ParseError,
+    handheld::Machine,
+    input::InputSource,
+    netpbm::ImageFormat,
+    runner::{self, DayResult},
+    solution::{self, Puzzle},
+};
+use cli::Command;
+use debug::{Debugger, Outcome};
+use format::Report;
+use std::{
+    io::{BufRead, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 fn main() {
-    let timer = std::time::Instant::now();
-    day1::run();
-    day2::run();
-    day3::run();
-    day4::run();
-    day5::run();
-    day6::run();
-    day7::run();
-    day8::run();
-    day9::run();
-    day10::run();
-    day11::run();
-    day12::run();
-    day13::run();
-    day14::run();
-    day15::run();
-    day16::run();
-    day17::run();
-    day18::run();
-    day19::run();
-    day20::run();
-    day21::run();
-    day22::run();
-    day23::run();
-    day24::run();
-    day25::run();
-    println!("{}us", timer.elapsed().as_micros());
+    let command = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(error) => {
+            eprintln!("error: {}\n\n{}", error, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    match command {
+        Command::Run {
+            days,
+            part,
+            input,
+            format,
+            jobs,
+            timeout,
+        } => {
+            let timer = std::time::Instant::now();
+            let mut report = Report::new(format);
+            let mut failed = false;
+            let mut handle = |result: Result<DayResult, String>| match result {
+                Ok(result) => {
+                    report.day(&result);
+                    failed |= result.parts.iter().any(|part| part.answer.is_err());
+                }
+                Err(message) => {
+                    eprintln!("{}", message);
+                    failed = true;
+                }
+            };
+            if jobs == 1 {
+                // results are printed as soon as each day is solved
+                for &day in &days {
+                    handle(run_day(day, &input, part, timeout));
+                }
+            } else {
+                runner::parallel_map(&days, jobs, |&day| run_day(day, &input, part, timeout))
+                    .into_iter()
+                    .for_each(handle);
+            }
+            report.finish(timer.elapsed(), jobs > 1);
+            if failed {
+                std::process::exit(1);
+            }
+        }
+        Command::Verify {
+            days,
+            input,
+            answers,
+            timeout,
+        } => {
+            let path = answers.unwrap_or_else(answers::default_path);
+            if !verify(&days, &input, path, timeout) {
+                std::process::exit(1);
+            }
+        }
+        Command::Bench {
+            days,
+            input,
+            iterations,
+            save,
+            baseline,
+            threshold,
+        } => {
+            let baseline = match baseline {
+                Some(path) => match read_baseline(&path) {
+                    Some(baseline) => Some(baseline),
+                    None => std::process::exit(1),
+                },
+                None => None,
+            };
+            let mut failed = false;
+            let mut regressions = 0;
+            let mut results = vec![];
+            for day in days {
+                let day_results = match bench_day(day, &input, iterations) {
+                    Ok(day_results) => day_results,
+                    Err(message) => {
+                        eprintln!("{}", message);
+                        failed = true;
+                        continue;
+                    }
+                };
+                for result in day_results {
+                    let change = baseline
+                        .as_ref()
+                        .and_then(|baseline| baseline.change(&result));
+                    if change.is_some_and(|change| change * 100.0 > threshold) {
+                        regressions += 1;
+                    }
+                    print_bench_result(&result, change, threshold);
+                    results.push(result);
+                }
+            }
+            if baseline.is_some() {
+                println!("{} regression(s) over {}%", regressions, threshold);
+            }
+            if let Some(path) = save {
+                let contents = Baseline::from_results(&results).to_string();
+                if let Err(error) = std::fs::write(&path, contents) {
+                    eprintln!("cannot write {} : {}", path.display(), error);
+                    failed = true;
+                }
+            }
+            if failed || regressions > 0 {
+                std::process::exit(1);
+            }
+        }
+        Command::Debug { day, input } => {
+            let machine = match with_input(day, &input, |_, contents| {
+                Machine::<day8::Instruction>::load(contents)
+            }) {
+                Ok(machine) => machine,
+                Err(message) => {
+                    eprintln!("{}", message);
+                    std::process::exit(1);
+                }
+            };
+            debug(Debugger::new(machine));
+        }
+        Command::Disasm { day, input, dot } => {
+            let result = with_input(day, &input, |_, contents| {
+                let instructions = day8::parse_input(contents)?;
+                let disassembly = day8::Disassembly::new(&instructions);
+                Ok(if dot {
+                    disassembly.to_dot()
+                } else {
+                    disassembly.to_string()
+                })
+            });
+            match result {
+                Ok(output) => print!("{}", output),
+                Err(message) => {
+                    eprintln!("{}", message);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Command::Search { pattern, input } => {
+            if let Err(message) = search(&pattern, &input) {
+                eprintln!("{}", message);
+                std::process::exit(1);
+            }
+        }
+        Command::Export {
+            directory,
+            input,
+            format,
+            options,
+        } => match export(&directory, &input, format, &options) {
+            Ok(count) => println!("{} image(s) written to {}", count, directory.display()),
+            Err(message) => {
+                eprintln!("{}", message);
+                std::process::exit(1);
+            }
+        },
+        Command::List => {
+            for puzzle in solution::REGISTRY.iter() {
+                println!(
+                    "day {} : {}, {} part(s)",
+                    puzzle.day(),
+                    puzzle.title(),
+                    puzzle.parts()
+                );
+            }
+        }
+        Command::Help => println!("{}", cli::USAGE),
+    }
+}
+
+/// Searches the image of day 20 for the pattern of the file at `path`.
+fn search(path: &Path, input: &InputSource) -> Result<(), String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|error| format!("cannot read {} : {}", path.display(), error))?;
+    let pattern = day20::Pattern::new(&contents)
+        .map_err(|error| format_parse_error(&path.display().to_string(), &contents, &error))?;
+    let picture = with_input(20, input, |_, contents| {
+        Ok(day20::Picture::new(day20::parse_input(contents)?))
+    })?
+    .map_err(|error| format!("day 20 : {}", error))?;
+    let search = picture.get_picture().search(&pattern);
+    for found in &search.matches {
+        println!(
+            "row {}, column {}, {}",
+            found.row, found.column, found.orientation
+        );
+    }
+    println!(
+        "{} match(es), roughness {}",
+        search.matches.len(),
+        search.roughness
+    );
+    Ok(())
+}
+
+/// Writes the images of day 20 to `directory`, returns how many were written.
+fn export(
+    directory: &Path,
+    input: &InputSource,
+    format: ImageFormat,
+    options: &RenderOptions,
+) -> Result<usize, String> {
+    let tiles = with_input(20, input, |_, contents| day20::parse_input(contents))?;
+    let mut images = tiles
+        .iter()
+        .map(|tile| (format!("tile-{}", tile.id()), tile.to_image()))
+        .collect::<Vec<_>>();
+    let picture = day20::Picture::new(tiles).map_err(|error| format!("day 20 : {}", error))?;
+    images.push(("picture".to_owned(), picture.to_image(options)));
+    images.push((
+        "monsters".to_owned(),
+        picture
+            .get_picture()
+            .highlight(&day20::Pattern::sea_monster())
+            .scaled(options.scale),
+    ));
+
+    std::fs::create_dir_all(directory)
+        .map_err(|error| format!("cannot create {} : {}", directory.display(), error))?;
+    for (name, image) in &images {
+        let path = directory.join(format!("{}.{}", name, format.extension()));
+        std::fs::write(&path, image.to_bytes(format))
+            .map_err(|error| format!("cannot write {} : {}", path.display(), error))?;
+    }
+    Ok(images.len())
+}
+
+/// Reads the input of the day and runs `f` on it, errors are returned as the message to print
+/// so days run on other threads can be reported in order.
+fn with_input<T>(
+    day: u8,
+    input: &InputSource,
+    f: impl FnOnce(&'static dyn Puzzle, &str) -> Result<T, ParseError>,
+) -> Result<T, String> {
+    let contents = input
+        .read(day)
+        .map_err(|error| format!("day {} : {}", day, error))?;
+    f(solution::get(day).unwrap(), &contents)
+        .map_err(|error| format_parse_error(&format!("day {}", day), &contents, &error))
+}
+
+fn run_day(
+    day: u8,
+    input: &InputSource,
+    part: Option<u8>,
+    timeout: Option<Duration>,
+) -> Result<DayResult, String> {
+    with_input(day, input, |puzzle, contents| match timeout {
+        Some(timeout) => runner::run_with_timeout(puzzle, contents.to_owned(), part, timeout),
+        None => runner::run(puzzle, contents, part),
+    })
+}
+
+fn bench_day(day: u8, input: &InputSource, iterations: usize) -> Result<Vec<BenchResult>, String> {
+    with_input(day, input, |puzzle, contents| {
+        bench::bench(puzzle, contents, iterations)
+    })
+}
+
+fn read_baseline(path: &Path) -> Option<Baseline> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(error) => {
+            eprintln!("cannot read {} : {}", path.display(), error);
+            return None;
+        }
+    };
+    match Baseline::parse(&contents) {
+        Ok(baseline) => Some(baseline),
+        Err(error) => {
+            eprintln!(
+                "{}",
+                format_parse_error(&path.display().to_string(), &contents, &error)
+            );
+            None
+        }
+    }
+}
+
+fn print_bench_result(result: &BenchResult, change: Option<f64>, threshold: f64) {
+    let micros = |duration: Duration| format!("{:.1}us", duration.as_secs_f64() * 1e6);
+    let stats = &result.stats;
+    let mut line = format!(
+        "day {} {} : min {}, median {}, p95 {}, stddev {}",
+        result.day,
+        result.phase,
+        micros(stats.min),
+        micros(stats.median),
+        micros(stats.p95),
+        micros(stats.stddev)
+    );
+    if let Some(change) = change {
+        line.push_str(&format!(", {:+.1}% vs baseline", change * 100.0));
+        if change * 100.0 > threshold {
+            line.push_str(", regression");
+        }
+    }
+    println!("{}", line);
+}
+
+/// Compares the answers of every day with the expected ones, returns whether all of them could
+/// be run and none is wrong.
+fn verify(days: &[u8], input: &InputSource, path: PathBuf, timeout: Option<Duration>) -> bool {
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(error) => {
+            eprintln!("cannot read {} : {}", path.display(), error);
+            return false;
+        }
+    };
+    let answers = match Answers::parse(&contents) {
+        Ok(answers) => answers,
+        Err(error) => {
+            eprintln!(
+                "{}",
+                format_parse_error(&path.display().to_string(), &contents, &error)
+            );
+            return false;
+        }
+    };
+
+    let (mut passed, mut failed, mut missing) = (0, 0, 0);
+    let mut success = true;
+    for &day in days {
+        let result = match run_day(day, input, None, timeout) {
+            Ok(result) => result,
+            Err(message) => {
+                eprintln!("{}", message);
+                success = false;
+                continue;
+            }
+        };
+        for part in result.parts {
+            let answer = match part.answer {
+                Ok(answer) => answer,
+                Err(error) => {
+                    failed += 1;
+                    println!("day {} part {} : fail, {}", day, part.part, error);
+                    continue;
+                }
+            };
+            match answers.check(day, part.part, &answer) {
+                Verdict::Pass => {
+                    passed += 1;
+                    println!("day {} part {} : pass", day, part.part);
+                }
+                Verdict::Fail(expected) => {
+                    failed += 1;
+                    println!(
+                        "day {} part {} : fail, expected {}, found {}",
+                        day, part.part, expected, answer
+                    );
+                }
+                Verdict::Missing => {
+                    missing += 1;
+                    println!("day {} part {} : missing, found {}", day, part.part, answer);
+                }
+            }
+        }
+    }
+    println!("{} passed, {} failed, {} missing", passed, failed, missing);
+    success && failed == 0
+}
+
+/// Reads the commands from stdin until `quit` or the end of the input.
+fn debug(mut debugger: Debugger<day8::Instruction>) {
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("(debug) ");
+        std::io::stdout().flush().unwrap();
+        let Some(Ok(line)) = lines.next() else {
+            println!();
+            break;
+        };
+        match debugger.execute(&line) {
+            Ok(Outcome::Continue(output)) if output.is_empty() => {}
+            Ok(Outcome::Continue(output)) => println!("{}", output),
+            Ok(Outcome::Quit) => break,
+            Err(message) => println!("error: {}", message),
+        }
+    }
+}
+
+/// The error followed by the offending line with the token underlined.
+fn format_parse_error(source: &str, input: &str, error: &ParseError) -> String {
+    let mut message = format!("{} : parse error at {}", source, error);
+    if let Some(line) = input.lines().nth(error.line - 1) {
+        let number = error.line.to_string();
+        message.push_str(&format!(
+            "\n{} | {}\n{} | {}{}",
+            number,
+            line,
+            " ".repeat(number.len()),
+            " ".repeat(error.column - 1),
+            "^".repeat(error.token.chars().count().max(1))
+        ));
+    }
+    message
 }
@@ -0,0 +1,218 @@
+use std::{fmt::Display, io::Write};
+
+pub type Rgb = [u8; 3];
+
+/// The Netpbm formats, written in their binary variants.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImageFormat {
+    /// Black and white, the dark pixels are black.
+    Pbm,
+    /// Shades of gray.
+    Pgm,
+    Ppm,
+}
+
+impl ImageFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "pbm" => Some(Self::Pbm),
+            "pgm" => Some(Self::Pgm),
+            "ppm" => Some(Self::Ppm),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Pbm => "pbm",
+            Self::Pgm => "pgm",
+            Self::Ppm => "ppm",
+        }
+    }
+}
+
+impl Display for ImageFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.extension())
+    }
+}
+
+/// Digits of 3 by 5 pixels, one row per item and the bits from left to right.
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b011, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+/// Width of a digit drawn by `Image::draw_number` along with the space after it.
+pub const DIGIT_WIDTH: usize = 4;
+pub const DIGIT_HEIGHT: usize = 5;
+
+/// Color image, row by row from the top left corner.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, background: Rgb) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![background; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Rgb {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Pixels out of the image are ignored, so the drawings are clipped.
+    pub fn set(&mut self, x: usize, y: usize, color: Rgb) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = color;
+        }
+    }
+
+    pub fn fill(&mut self, x: usize, y: usize, width: usize, height: usize, color: Rgb) {
+        for j in y..(y + height).min(self.height) {
+            for i in x..(x + width).min(self.width) {
+                self.set(i, j, color);
+            }
+        }
+    }
+
+    /// Draws the decimal digits of `value` from the top left corner at `x` and `y`, clipped to
+    /// `max_x`.
+    pub fn draw_number(&mut self, x: usize, y: usize, max_x: usize, value: u64, color: Rgb) {
+        for (index, digit) in value.to_string().bytes().enumerate() {
+            let rows = DIGITS[(digit - b'0') as usize];
+            let left = x + index * DIGIT_WIDTH;
+            for (j, row) in rows.iter().enumerate() {
+                for i in 0..3 {
+                    if row >> (2 - i) & 1 == 1 && left + i < max_x {
+                        self.set(left + i, y + j, color);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Every pixel becomes a square of `factor` pixels on a side.
+    pub fn scaled(&self, factor: usize) -> Self {
+        let mut image = Self::new(self.width * factor, self.height * factor, [0; 3]);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                image.fill(x * factor, y * factor, factor, factor, self.get(x, y));
+            }
+        }
+        image
+    }
+
+    pub fn write(&self, format: ImageFormat, out: &mut impl Write) -> std::io::Result<()> {
+        match format {
+            ImageFormat::Pbm => {
+                write!(out, "P4\n{} {}\n", self.width, self.height)?;
+                for row in self.pixels.chunks(self.width.max(1)) {
+                    let mut bytes = vec![0u8; self.width.div_ceil(8)];
+                    for (x, &pixel) in row.iter().enumerate() {
+                        if luminance(pixel) < 128 {
+                            bytes[x / 8] |= 0x80 >> (x % 8);
+                        }
+                    }
+                    out.write_all(&bytes)?;
+                }
+            }
+            ImageFormat::Pgm => {
+                write!(out, "P5\n{} {}\n255\n", self.width, self.height)?;
+                let bytes = self.pixels.iter().map(|&pixel| luminance(pixel));
+                out.write_all(&bytes.collect::<Vec<_>>())?;
+            }
+            ImageFormat::Ppm => {
+                write!(out, "P6\n{} {}\n255\n", self.width, self.height)?;
+                out.write_all(&self.pixels.concat())?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn to_bytes(&self, format: ImageFormat) -> Vec<u8> {
+        let mut bytes = vec![];
+        self.write(format, &mut bytes).unwrap();
+        bytes
+    }
+}
+
+fn luminance([r, g, b]: Rgb) -> u8 {
+    ((299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: Rgb = [255; 3];
+    const RED: Rgb = [255, 0, 0];
+
+    #[test]
+    fn test_formats() {
+        let mut image = Image::new(9, 2, WHITE);
+        image.set(0, 0, [0; 3]);
+        image.set(8, 1, RED);
+        image.set(9, 1, RED);
+
+        let mut expected = b"P4\n9 2\n".to_vec();
+        expected.extend([0x80, 0x00, 0x00, 0x80]);
+        assert_eq!(image.to_bytes(ImageFormat::Pbm), expected);
+
+        let pgm = image.to_bytes(ImageFormat::Pgm);
+        assert!(pgm.starts_with(b"P5\n9 2\n255\n"));
+        assert_eq!(pgm[11..].len(), 18);
+        assert_eq!((pgm[11], pgm[12], pgm[28]), (0, 255, 76));
+
+        let ppm = image.to_bytes(ImageFormat::Ppm);
+        assert!(ppm.starts_with(b"P6\n9 2\n255\n"));
+        assert_eq!(&ppm[ppm.len() - 3..], &RED);
+        assert_eq!(ImageFormat::from_name("ppm"), Some(ImageFormat::Ppm));
+    }
+
+    #[test]
+    fn test_drawing() {
+        let mut image = Image::new(2, 1, WHITE);
+        image.set(1, 0, RED);
+        let scaled = image.scaled(3);
+        assert_eq!((scaled.width(), scaled.height()), (6, 3));
+        assert_eq!((scaled.get(2, 2), scaled.get(3, 0)), (WHITE, RED));
+
+        let mut image = Image::new(8, 5, WHITE);
+        image.draw_number(0, 0, 7, 17, RED);
+        let rows = (0..5)
+            .map(|y| {
+                (0..8)
+                    .map(|x| if image.get(x, y) == RED { '#' } else { '.' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            [".#..###.", "##....#.", ".#...#..", ".#...#..", "###..#.."]
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use crate::{
+    cancel::CancelFlag,
+    error::{ParseError, SolveError},
+    solution::{Answer, Parsed, Puzzle},
+};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, RecvTimeoutError},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Answer of a part along with the time spent solving it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PartResult {
+    pub part: u8,
+    pub answer: Result<Answer, SolveError>,
+    pub solve_time: Duration,
+}
+
+/// Result of a day : its parts share the same parsed input.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DayResult {
+    pub day: u8,
+    pub parse_time: Duration,
+    pub parts: Vec<PartResult>,
+}
+
+impl DayResult {
+    /// Time spent parsing and solving the parts.
+    pub fn total_time(&self) -> Duration {
+        self.parse_time
+            + self
+                .parts
+                .iter()
+                .map(|part| part.solve_time)
+                .sum::<Duration>()
+    }
+}
+
+/// Parses `input` then solves every part of the puzzle, or only `part` when given.
+pub fn run(puzzle: &dyn Puzzle, input: &str, part: Option<u8>) -> Result<DayResult, ParseError> {
+    let timer = Instant::now();
+    let parsed = puzzle.parse(input)?;
+    let parse_time = timer.elapsed();
+    let parts = selected_parts(puzzle, part)
+        .map(|part| solve_part(parsed.as_ref(), part))
+        .collect();
+    Ok(DayResult {
+        day: puzzle.day(),
+        parse_time,
+        parts,
+    })
+}
+
+enum Event {
+    Parsed(Result<Duration, ParseError>),
+    Solved(PartResult),
+}
+
+/// Same as `run` but gives up once `timeout` has elapsed : the parts left are reported as timed
+/// out and the thread solving them is cancelled at its next checkpoint.
+pub fn run_with_timeout(
+    puzzle: &'static dyn Puzzle,
+    input: String,
+    part: Option<u8>,
+    timeout: Duration,
+) -> Result<DayResult, ParseError> {
+    let deadline = Instant::now() + timeout;
+    let flag = CancelFlag::new();
+    let (sender, receiver) = mpsc::channel();
+    // the worker is detached, a solver without checkpoints keeps running in the background
+    let worker = {
+        let flag = flag.clone();
+        thread::spawn(move || {
+            flag.install();
+            let timer = Instant::now();
+            let parsed = match puzzle.parse(&input) {
+                Ok(parsed) => parsed,
+                Err(error) => {
+                    let _ = sender.send(Event::Parsed(Err(error)));
+                    return;
+                }
+            };
+            let _ = sender.send(Event::Parsed(Ok(timer.elapsed())));
+            for part in selected_parts(puzzle, part) {
+                if sender
+                    .send(Event::Solved(solve_part(parsed.as_ref(), part)))
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        })
+    };
+
+    let mut parse_time = None;
+    let mut parts = vec![];
+    let mut remaining = selected_parts(puzzle, part).collect::<Vec<_>>();
+    while parse_time.is_none() || !remaining.is_empty() {
+        match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(Event::Parsed(time)) => parse_time = Some(time?),
+            Ok(Event::Solved(result)) => {
+                remaining.retain(|&part| part != result.part);
+                parts.push(result);
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                flag.cancel();
+                let spent = *parse_time.get_or_insert(timeout)
+                    + parts.iter().map(|part| part.solve_time).sum::<Duration>();
+                // the part being solved used up the rest of the budget, the others did not start
+                for (index, part) in remaining.drain(..).enumerate() {
+                    parts.push(PartResult {
+                        part,
+                        answer: Err(SolveError::TimedOut),
+                        solve_time: if index == 0 {
+                            timeout.saturating_sub(spent)
+                        } else {
+                            Duration::ZERO
+                        },
+                    });
+                }
+            }
+            // the worker can only hang up early by panicking
+            Err(RecvTimeoutError::Disconnected) => match worker.join() {
+                Err(panic) => std::panic::resume_unwind(panic),
+                Ok(()) => unreachable!("the worker stopped before sending every result"),
+            },
+        }
+    }
+    Ok(DayResult {
+        day: puzzle.day(),
+        parse_time: parse_time.unwrap(),
+        parts,
+    })
+}
+
+/// Every part of the puzzle, or only `part` when given.
+fn selected_parts(puzzle: &dyn Puzzle, part: Option<u8>) -> impl Iterator<Item = u8> {
+    (1..=puzzle.parts()).filter(move |&current_part| part.is_none_or(|part| part == current_part))
+}
+
+fn solve_part(parsed: &dyn Parsed, part: u8) -> PartResult {
+    let timer = Instant::now();
+    let answer = parsed.solve(part);
+    PartResult {
+        part,
+        answer,
+        solve_time: timer.elapsed(),
+    }
+}
+
+/// Applies `f` to every item on `jobs` threads, the results are in the order of `items`.
+pub fn parallel_map<T: Sync, R: Send>(
+    items: &[T],
+    jobs: usize,
+    f: impl Fn(&T) -> R + Sync,
+) -> Vec<R> {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new((0..items.len()).map(|_| None).collect::<Vec<_>>());
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, items.len().max(1)) {
+            scope.spawn(|| loop {
+                // every thread takes the next item until there is none left
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(index) else {
+                    break;
+                };
+                let result = f(item);
+                results.lock().unwrap()[index] = Some(result);
+            });
+        }
+    });
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(Option::unwrap)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution;
+
+    #[test]
+    fn test_run() {
+        let puzzle = solution::get(13).unwrap();
+        let result = run(puzzle, "939\n7,13,x,x,59,x,31,19", None).unwrap();
+        assert_eq!(result.day, 13);
+        let answers = result
+            .parts
+            .iter()
+            .map(|part| (part.part, part.answer.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            answers,
+            vec![
+                (1, Ok(Answer::Integer(295))),
+                (2, Ok(Answer::Integer(1068781)))
+            ]
+        );
+
+        let result = run(puzzle, "939\n7,13,x,x,59,x,31,19", Some(2)).unwrap();
+        assert_eq!(result.parts.len(), 1);
+        assert_eq!(result.parts[0].part, 2);
+    }
+
+    #[test]
+    fn test_run_with_timeout() {
+        let puzzle = solution::get(13).unwrap();
+        let input = "939\n7,13,x,x,59,x,31,19".to_owned();
+        let result = run_with_timeout(puzzle, input, None, Duration::from_secs(60)).unwrap();
+        assert_eq!(result.parts.len(), 2);
+        assert_eq!(result.parts[1].answer, Ok(Answer::Integer(1068781)));
+        assert!(run_with_timeout(puzzle, "939".to_owned(), None, Duration::from_secs(60)).is_err());
+
+        // a game of combat which never ends
+        let puzzle = solution::get(22).unwrap();
+        let input = "Player 1:\n43\n19\n\nPlayer 2:\n2\n29\n14".to_owned();
+        let result = run_with_timeout(puzzle, input, Some(1), Duration::from_millis(10)).unwrap();
+        assert_eq!(result.parts.len(), 1);
+        assert_eq!(result.parts[0].answer, Err(SolveError::TimedOut));
+    }
+
+    #[test]
+    fn test_parallel_map() {
+        let items = (0..20u64).collect::<Vec<_>>();
+        assert_eq!(
+            parallel_map(&items, 4, |&item| item * item),
+            items.iter().map(|item| item * item).collect::<Vec<_>>()
+        );
+        assert!(parallel_map(&[] as &[u8], 4, |&item| item).is_empty());
+    }
+}
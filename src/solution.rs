@@ -0,0 +1,223 @@
+use std::{fmt::Display, marker::PhantomData};
+
+use crate::{
+    bigint::BigInt,
+    day1, day10, day11, day12, day13, day14, day15, day16, day17, day18, day19, day2, day20, day21,
+    day22, day23, day24, day25, day3, day4, day5, day6, day7, day8, day9,
+    error::{ParseError, SolveError},
+};
+
+/// A day of the calendar : the input is parsed once and shared by both parts.
+pub trait Solution {
+    /// Parsed puzzle input, it may borrow from the raw input.
+    type Input<'a>;
+    type Output1: IntoAnswer;
+    type Output2: IntoAnswer;
+
+    const DAY: u8;
+    const TITLE: &'static str;
+    /// Number of parts, the last day only has one.
+    const PARTS: u8 = 2;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError>;
+    fn part_1(input: &Self::Input<'_>) -> Self::Output1;
+    fn part_2(input: &Self::Input<'_>) -> Self::Output2;
+}
+
+/// Answer of a part, whatever the type returned by the solution.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Answer {
+    Integer(i128),
+    Text(String),
+    /// Returned by parts without a puzzle.
+    None,
+}
+
+impl Answer {
+    /// Name of the answer type, as reported by the machine readable outputs.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Answer::Integer(_) => "integer",
+            Answer::Text(_) => "text",
+            Answer::None => "none",
+        }
+    }
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Integer(value) => write!(f, "{}", value),
+            Answer::Text(value) => write!(f, "{}", value),
+            Answer::None => write!(f, "-"),
+        }
+    }
+}
+
+macro_rules! integer_answer {
+    ($($integer:ty),*) => {
+        $(
+            impl From<$integer> for Answer {
+                fn from(value: $integer) -> Self {
+                    Answer::Integer(value as i128)
+                }
+            }
+        )*
+    };
+}
+
+integer_answer!(u16, u32, u64, u128, usize, i32, i64);
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Answer::Text(value)
+    }
+}
+
+/// An integer answer when it fits, its decimal digits otherwise.
+impl From<BigInt> for Answer {
+    fn from(value: BigInt) -> Self {
+        match value.to_i128() {
+            Some(value) => Answer::Integer(value),
+            None => Answer::Text(value.to_string()),
+        }
+    }
+}
+
+impl From<()> for Answer {
+    fn from(_: ()) -> Self {
+        Answer::None
+    }
+}
+
+/// Output of a part : any type convertible to an `Answer`, or a `Result` of one for the parts
+/// which can fail.
+pub trait IntoAnswer {
+    fn into_answer(self) -> Result<Answer, SolveError>;
+}
+
+impl<T: Into<Answer>> IntoAnswer for T {
+    fn into_answer(self) -> Result<Answer, SolveError> {
+        Ok(self.into())
+    }
+}
+
+impl<T: Into<Answer>> IntoAnswer for Result<T, SolveError> {
+    fn into_answer(self) -> Result<Answer, SolveError> {
+        self.map(Into::into)
+    }
+}
+
+/// Object safe view of a `Solution` so every day can be stored in the registry.
+pub trait Puzzle: Sync {
+    fn day(&self) -> u8;
+    fn title(&self) -> &'static str;
+    fn parts(&self) -> u8;
+    fn parse<'a>(&self, input: &'a str) -> Result<Box<dyn Parsed + 'a>, ParseError>;
+}
+
+/// Parsed input of a `Puzzle`, ready to solve any of its parts.
+pub trait Parsed {
+    /// Panics if the part does not exist.
+    fn solve(&self, part: u8) -> Result<Answer, SolveError>;
+}
+
+struct Registered<S>(PhantomData<fn() -> S>);
+
+struct ParsedInput<'a, S: Solution>(S::Input<'a>);
+
+impl<S: Solution + 'static> Puzzle for Registered<S> {
+    fn day(&self) -> u8 {
+        S::DAY
+    }
+
+    fn title(&self) -> &'static str {
+        S::TITLE
+    }
+
+    fn parts(&self) -> u8 {
+        S::PARTS
+    }
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Box<dyn Parsed + 'a>, ParseError> {
+        Ok(Box::new(ParsedInput::<S>(S::parse(input)?)))
+    }
+}
+
+impl<'a, S: Solution> Parsed for ParsedInput<'a, S> {
+    fn solve(&self, part: u8) -> Result<Answer, SolveError> {
+        match part {
+            1 => S::part_1(&self.0).into_answer(),
+            2 if S::PARTS == 2 => S::part_2(&self.0).into_answer(),
+            _ => panic!("day {} has no part {}", S::DAY, part),
+        }
+    }
+}
+
+macro_rules! registry {
+    ($($solution:ty),* $(,)?) => {
+        [$(&Registered::<$solution>(PhantomData)),*]
+    };
+}
+
+/// Every solution, in day order.
+pub static REGISTRY: [&dyn Puzzle; 25] = registry![
+    day1::Day1,
+    day2::Day2,
+    day3::Day3,
+    day4::Day4,
+    day5::Day5,
+    day6::Day6,
+    day7::Day7,
+    day8::Day8,
+    day9::Day9,
+    day10::Day10,
+    day11::Day11,
+    day12::Day12,
+    day13::Day13,
+    day14::Day14,
+    day15::Day15,
+    day16::Day16,
+    day17::Day17,
+    day18::Day18,
+    day19::Day19,
+    day20::Day20,
+    day21::Day21,
+    day22::Day22,
+    day23::Day23,
+    day24::Day24,
+    day25::Day25,
+];
+
+pub fn get(day: u8) -> Option<&'static dyn Puzzle> {
+    REGISTRY.iter().copied().find(|puzzle| puzzle.day() == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_order() {
+        for (index, puzzle) in REGISTRY.iter().enumerate() {
+            assert_eq!(puzzle.day() as usize, index + 1);
+        }
+        assert_eq!(get(8).unwrap().title(), "Handheld Halting");
+        assert!(get(26).is_none());
+    }
+
+    #[test]
+    fn test_solve_through_registry() {
+        let input = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
+        let parsed = get(8).unwrap().parse(input).unwrap();
+        assert_eq!(parsed.solve(1), Ok(Answer::Integer(5)));
+        assert_eq!(parsed.solve(2), Ok(Answer::Integer(8)));
+    }
+
+    #[test]
+    fn test_parse_error_through_registry() {
+        let error = get(8).unwrap().parse("nop +0\nfoo +1").err().unwrap();
+        assert_eq!((error.line, error.column), (2, 1));
+        assert_eq!(error.token, "foo");
+    }
+}
@@ -0,0 +1,21 @@
+use aoc2020::{
+    day13, day18,
+    solution::{self, Answer},
+};
+
+#[test]
+fn test_day_api() {
+    let problem = day13::Problem::new("939\n7,13,x,x,59,x,31,19").unwrap();
+    assert_eq!(problem.solve_2(), 1068781);
+
+    let operation = day18::Operation::parse("2 * 3 + (4 * 5)", true).unwrap();
+    assert_eq!(operation.solve(), Ok(46));
+}
+
+#[test]
+fn test_registry_api() {
+    let puzzle = solution::get(13).unwrap();
+    let parsed = puzzle.parse("939\n7,13,x,x,59,x,31,19").unwrap();
+    assert_eq!(parsed.solve(1), Ok(Answer::Integer(295)));
+    assert_eq!(parsed.solve(2), Ok(Answer::Integer(1068781)));
+}